@@ -0,0 +1,14 @@
+#version 100
+precision lowp float;
+varying vec2 uv;
+varying vec4 color;
+varying vec4 light;
+uniform sampler2D Texture;
+uniform vec4 ambient;        // how bright the sky is right now
+uniform float minBrightness; // nothing is drawn darker than this
+
+void main() {
+    vec3 lit = max(light.yzw, light.x * ambient.rgb);
+    lit = max(lit, vec3(minBrightness));
+    gl_FragColor = texture2D(Texture, uv) * color * vec4(lit, 1.0);
+}
@@ -0,0 +1,17 @@
+#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+attribute vec4 normal;
+varying lowp vec2 uv;
+varying lowp vec4 color;
+varying lowp vec4 light;
+uniform mat4 Model;
+uniform mat4 Projection;
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    uv = texcoord;
+    color = color0 / 255.0;
+    // the normal carries the cell's light: sky, then red, green and blue block light
+    light = normal;
+}
@@ -4,14 +4,49 @@ uniform vec2 canvasSize;
 uniform sampler2D Texture;
 uniform vec4 startColor; // Specify the starting color
 uniform vec4 endColor;   // Specify the ending color
+uniform vec2 screenSize; // Size of the framebuffer in pixels
+uniform vec2 sunPos;     // Sun and moon in 0..1 screen space, y up
+uniform vec2 moonPos;
+uniform float starAlpha; // 0 during the day, 1 at night
+
+float hash(vec2 p) {
+    return fract(sin(dot(p, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+// soft disc with a glow around it
+float body(vec2 uv, vec2 pos, float radius, float glow) {
+    vec2 d = (uv - pos) * vec2(screenSize.x / screenSize.y, 1.0);
+    float dist = length(d);
+    float disc = 1.0 - smoothstep(radius * 0.9, radius, dist);
+    float halo = glow * (1.0 - smoothstep(radius, radius * 4.0, dist));
+    return max(disc, halo);
+}
 
 void main() {
     vec2 coord = gl_FragCoord.xy / canvasSize; // Normalize to [0,1] using canvas size
+    vec4 gradientColor = vec4(1, 1, 1, 1); // White
     if (abs(coord.x) < 1. && abs(coord.y) < 1.) {
         coord = (coord + 1.) / 2.;
-        vec4 gradientColor = mix(startColor, endColor, 1.0 - coord.y);
-        gl_FragColor = gradientColor;
-    } else {
-        gl_FragColor = vec4(1, 1, 1, 1); // White
+        gradientColor = mix(startColor, endColor, 1.0 - coord.y);
     }
-}
\ No newline at end of file
+
+    vec2 uv = gl_FragCoord.xy / screenSize;
+    vec3 color = gradientColor.rgb;
+    float alpha = gradientColor.a;
+
+    // stars, fading towards the horizon
+    float star = step(0.9975, hash(floor(gl_FragCoord.xy / 2.0)));
+    float starLight = star * starAlpha * smoothstep(0.1, 0.6, uv.y);
+    color = mix(color, vec3(1.0), starLight);
+    alpha = max(alpha, starLight);
+
+    float sun = body(uv, sunPos, 0.04, 0.25);
+    color = mix(color, vec3(1.0, 0.9, 0.65), sun);
+    alpha = max(alpha, sun);
+
+    float moon = body(uv, moonPos, 0.03, 0.1);
+    color = mix(color, vec3(0.8, 0.85, 0.95), moon);
+    alpha = max(alpha, moon);
+
+    gl_FragColor = vec4(color, alpha);
+}
@@ -0,0 +1,58 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+// folders under src/assets whose PNGs are packed into the texture atlas
+const ATLAS_DIRS: [&str; 4] = ["backgrounds", "overlays", "sprites", "ui"];
+
+// every png in `dir` and below, with its name relative to src/assets
+fn collect_pngs(assets: &Path, dir: &Path, found: &mut Vec<(String, PathBuf)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_pngs(assets, &path, found);
+        } else if path.extension().is_some_and(|ext| ext == "png") {
+            let name = path
+                .strip_prefix(assets)
+                .unwrap()
+                .with_extension("")
+                .to_string_lossy()
+                .replace('\\', "/");
+            found.push((name, path.canonicalize().unwrap()));
+        }
+    }
+}
+
+// embeds the atlas images so new ones only have to be dropped into a folder
+fn main() {
+    let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/assets");
+    println!("cargo:rerun-if-changed={}", assets.display());
+
+    let mut found: Vec<(String, PathBuf)> = Vec::new();
+    for dir in ATLAS_DIRS {
+        let dir = assets.join(dir);
+        if dir.is_dir() {
+            println!("cargo:rerun-if-changed={}", dir.display());
+            collect_pngs(&assets, &dir, &mut found);
+        }
+    }
+    // sorted so the atlas comes out the same on every machine
+    found.sort();
+
+    let mut source = String::from("pub const ATLAS_IMAGES: &[(&str, &[u8])] = &[\n");
+    for (name, path) in found {
+        source.push_str(&format!(
+            "    ({:?}, include_bytes!({:?})),\n",
+            name,
+            path.display().to_string()
+        ));
+    }
+    source.push_str("];\n");
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("atlas_images.rs");
+    fs::write(out, source).unwrap();
+}
@@ -0,0 +1,487 @@
+use crate::camera::CameraSettings;
+use crate::cli::LaunchOptions;
+use crate::debug::draw_debug_text;
+use crate::game::Game;
+use crate::game::QUICKSAVE_PATH;
+use crate::inspector::draw_inspector;
+use crate::profiler::scope;
+use crate::save::load_world;
+use crate::save::save_world;
+use crate::settings::is_bindable;
+use crate::settings::key_name;
+use crate::settings::Action;
+use crate::settings::Settings;
+use crate::settings::MAX_UI_SCALE;
+use crate::settings::MAX_WINDOW;
+use crate::settings::MIN_UI_SCALE;
+use crate::settings::MIN_WINDOW;
+use crate::settings::SETTINGS_PATH;
+use crate::ui::Anchor;
+use crate::ui::Ui;
+use crate::world::generate_world;
+use crate::BUILD_VERSION;
+use macroquad::miniquad::date;
+use macroquad::prelude::*;
+use std::fs;
+
+// the extension worlds are saved with, what the world select lists
+const SAVE_EXTENSION: &str = "vxw";
+const MAX_SEED_LENGTH: usize = 24;
+// rows of saves the world select shows at once
+const SAVE_ROWS: usize = 8;
+const MENU_WIDTH: f32 = 280.0;
+
+// what the loading screen is waiting on
+enum LoadTask {
+    Generate(u32),
+    Open(String),
+}
+
+enum State {
+    Title,
+    WorldSelect,
+    Playing,
+    // the world is drawn but doesn't update underneath the pause menu
+    Paused,
+    Settings,
+    KeyBindings,
+    Loading(LoadTask),
+}
+
+// everything around a game: the menus on top of it and the screens before one
+// exists. states stack, so settings goes back to whichever menu opened it
+pub struct App {
+    states: Vec<State>,
+    game: Option<Game>,
+    settings: Settings,
+    ui: Ui,
+    font: Font,
+    // typed on the title screen, a random seed when empty
+    seed_input: String,
+    // save files found when the world select opened, and the highlighted one
+    saves: Vec<String>,
+    selected_save: usize,
+    // the last thing that went wrong or happened, shown under the menu
+    message: Option<String>,
+    // the loading screen has been drawn, so the slow part can start
+    loading_shown: bool,
+    // waiting for the key to bind this to
+    rebinding: Option<Action>,
+    // --debug was given, for this run only so it never ends up in the settings
+    debug: bool,
+    pub quit: bool,
+}
+
+impl App {
+    // a world given on the command line starts loading straight away, with the
+    // title screen underneath for when it fails or is left
+    pub fn new(font: Font, options: LaunchOptions) -> Self {
+        let (settings, problems) = Settings::load(SETTINGS_PATH);
+        for problem in &problems {
+            eprintln!("{}", problem);
+        }
+        settings.apply_bindings();
+        let mut states = vec![State::Title];
+        if let Some(path) = options.open {
+            states.push(State::Loading(LoadTask::Open(path)));
+        } else if let Some(seed) = options.seed {
+            states.push(State::Loading(LoadTask::Generate(seed)));
+        }
+        App {
+            states,
+            game: None,
+            settings,
+            ui: Ui::new(Some(font.clone())),
+            font,
+            seed_input: String::new(),
+            saves: Vec::new(),
+            selected_save: 0,
+            message: None,
+            loading_shown: false,
+            rebinding: None,
+            debug: options.debug,
+            quit: false,
+        }
+    }
+
+    // written whenever a settings menu closes and when the game quits, along
+    // with whatever size the window was left at
+    fn save_settings(&mut self) {
+        if !self.settings.fullscreen {
+            self.settings.window_width = (screen_width() as u32).clamp(MIN_WINDOW.0, MAX_WINDOW.0);
+            self.settings.window_height =
+                (screen_height() as u32).clamp(MIN_WINDOW.1, MAX_WINDOW.1);
+        }
+        if let Err(err) = self.settings.save(SETTINGS_PATH) {
+            self.message = Some(err);
+        }
+        if let Some(game) = self.game.as_mut() {
+            self.settings.apply_camera(&mut game.world.camera, false);
+        }
+    }
+
+    fn close_settings(&mut self) {
+        self.rebinding = None;
+        self.save_settings();
+        self.pop();
+    }
+
+    fn state(&self) -> &State {
+        return self.states.last().unwrap();
+    }
+
+    fn push(&mut self, state: State) {
+        self.states.push(state);
+        self.ui.reset_focus();
+        self.loading_shown = false;
+    }
+
+    fn pop(&mut self) {
+        if self.states.len() > 1 {
+            self.states.pop();
+        }
+        self.ui.reset_focus();
+    }
+
+    // replaces the whole stack, when starting or leaving a game
+    fn reset(&mut self, state: State) {
+        self.states.clear();
+        self.push(state);
+    }
+
+    // everything but the menus, which happen while they're drawn
+    pub fn update(&mut self) {
+        match self.state() {
+            State::Playing => {
+                let game = self.game.as_mut().unwrap();
+                if is_key_pressed(KeyCode::Escape) && !game.console.open {
+                    self.push(State::Paused);
+                    return;
+                }
+                game.update();
+            }
+            State::Loading(_) => {
+                if self.loading_shown {
+                    self.finish_loading();
+                }
+            }
+            State::Paused | State::WorldSelect => {
+                if is_key_pressed(KeyCode::Escape) {
+                    self.pop();
+                }
+            }
+            State::Settings => {
+                if is_key_pressed(KeyCode::Escape) {
+                    self.close_settings();
+                }
+            }
+            State::KeyBindings => match self.rebinding {
+                // escape cancels, keys that can't be bound are ignored
+                Some(action) => {
+                    if let Some(key) = get_last_key_pressed() {
+                        if is_bindable(key) {
+                            self.settings.bind(action, key);
+                            self.settings.apply_bindings();
+                            self.rebinding = None;
+                        } else if key == KeyCode::Escape {
+                            self.rebinding = None;
+                        }
+                    }
+                }
+                None => {
+                    if is_key_pressed(KeyCode::Escape) {
+                        self.close_settings();
+                    }
+                }
+            },
+            State::Title => {}
+        }
+    }
+
+    // generating or loading takes a while, so it happens the frame after the
+    // loading screen was first drawn
+    fn finish_loading(&mut self) {
+        let State::Loading(task) = self.states.pop().unwrap() else {
+            return;
+        };
+        let world = match task {
+            LoadTask::Generate(seed) => Ok(generate_world(seed)),
+            LoadTask::Open(path) => load_world(&path),
+        };
+        match world {
+            Ok(mut world) => {
+                self.settings.apply_camera(&mut world.camera, true);
+                if self.debug {
+                    world.debug.collision = true;
+                    world.debug.chunks = true;
+                    world.debug.entities = true;
+                }
+                self.game = Some(Game::new(world));
+                self.reset(State::Playing);
+            }
+            Err(err) => {
+                self.ui.reset_focus();
+                self.message = Some(err);
+            }
+        }
+    }
+
+    pub fn render(&mut self) {
+        let playing = matches!(self.state(), State::Playing);
+        self.ui.scale = self.settings.ui_scale;
+        // the game has the keyboard while it's being played, and so does
+        // whatever key is about to be bound
+        self.ui.begin(!playing && self.rebinding.is_none());
+
+        // the world stays on screen behind the pause menu and its settings
+        if let Some(game) = self.game.as_mut() {
+            game.render(playing, &mut self.ui);
+            let _scope = scope("hud");
+            if self.settings.debug_text || self.debug {
+                draw_debug_text(game, self.font.clone());
+            }
+            if playing && !game.console.open {
+                draw_inspector(&game.world, &self.font);
+            }
+            game.console.render(&self.font);
+        }
+
+        match self.state() {
+            State::Playing => {}
+            State::Loading(_) => {
+                clear_background(BLACK);
+                self.ui.begin_panel("loading", Anchor::Center, 200.0);
+                self.ui.label("loading...");
+                self.ui.end_panel("loading");
+                self.loading_shown = true;
+            }
+            State::Title => {
+                clear_background(BLACK);
+                self.title_menu();
+            }
+            State::WorldSelect => {
+                clear_background(BLACK);
+                self.world_select();
+            }
+            State::Paused => {
+                shade_screen();
+                self.pause_menu();
+            }
+            State::Settings | State::KeyBindings => {
+                if self.game.is_some() {
+                    shade_screen();
+                } else {
+                    clear_background(BLACK);
+                }
+                if let State::Settings = self.state() {
+                    self.settings_menu();
+                } else {
+                    self.key_bindings_menu();
+                }
+            }
+        }
+        self.ui.end();
+    }
+
+    fn show_message(&mut self) {
+        if let Some(message) = &self.message {
+            self.ui.space(4.0);
+            self.ui.label(message);
+        }
+    }
+
+    fn title_menu(&mut self) {
+        self.ui.begin_panel("title", Anchor::Center, MENU_WIDTH);
+        self.ui.heading(&format!("Vexalab {}", BUILD_VERSION));
+        let mut new_world = self.ui.button("new world");
+        new_world |= self
+            .ui
+            .text_input("seed", &mut self.seed_input, "random", MAX_SEED_LENGTH);
+        self.ui
+            .tooltip("a number, or any text to turn into one. empty picks one at random");
+        let load = self.ui.button("load world");
+        let settings = self.ui.button("settings");
+        let quit = self.ui.button("quit");
+        self.show_message();
+        self.ui.end_panel("title");
+
+        if new_world {
+            self.message = None;
+            let seed = seed_from_text(&self.seed_input);
+            self.push(State::Loading(LoadTask::Generate(seed)));
+        } else if load {
+            self.message = None;
+            self.saves = find_saves();
+            self.selected_save = 0;
+            self.push(State::WorldSelect);
+        } else if settings {
+            self.push(State::Settings);
+        } else if quit {
+            self.save_settings();
+            self.quit = true;
+        }
+    }
+
+    fn world_select(&mut self) {
+        self.ui.begin_panel("worlds", Anchor::Center, MENU_WIDTH);
+        if self.saves.is_empty() {
+            self.ui.heading("no saved worlds");
+        } else {
+            self.ui.heading("load world");
+        }
+        let mut chosen = self
+            .ui
+            .list("saves", &self.saves, &mut self.selected_save, SAVE_ROWS);
+        self.ui.begin_row();
+        if !self.saves.is_empty() && self.ui.button("open") {
+            chosen = Some(self.selected_save);
+        }
+        let back = self.ui.button("back");
+        self.ui.end_row();
+        self.show_message();
+        self.ui.end_panel("worlds");
+
+        if let Some(path) = chosen.and_then(|index| self.saves.get(index)) {
+            self.message = None;
+            let path = path.clone();
+            self.push(State::Loading(LoadTask::Open(path)));
+        } else if back {
+            self.pop();
+        }
+    }
+
+    fn pause_menu(&mut self) {
+        self.ui.begin_panel("paused", Anchor::Center, MENU_WIDTH);
+        self.ui.heading("paused");
+        let resume = self.ui.button("resume");
+        let save = self.ui.button("save");
+        self.ui.tooltip(QUICKSAVE_PATH);
+        let settings = self.ui.button("settings");
+        let quit = self.ui.button("quit to title");
+        self.show_message();
+        self.ui.end_panel("paused");
+
+        if resume {
+            self.message = None;
+            self.pop();
+        } else if save {
+            let world = &self.game.as_ref().unwrap().world;
+            self.message = Some(match save_world(world, QUICKSAVE_PATH) {
+                Ok(()) => format!("saved to {}", QUICKSAVE_PATH),
+                Err(err) => err,
+            });
+        } else if settings {
+            self.push(State::Settings);
+        } else if quit {
+            self.message = None;
+            self.game = None;
+            self.reset(State::Title);
+        }
+    }
+
+    fn settings_menu(&mut self) {
+        let camera = CameraSettings::default();
+        let settings = &mut self.settings;
+        self.ui.begin_panel("settings", Anchor::Center, MENU_WIDTH);
+        self.ui.heading("settings");
+        if self.ui.checkbox("fullscreen", &mut settings.fullscreen) {
+            set_fullscreen(settings.fullscreen);
+        }
+        self.ui.checkbox("vsync", &mut settings.vsync);
+        self.ui
+            .tooltip("takes effect the next time the game starts");
+        self.ui.checkbox("debug text", &mut settings.debug_text);
+        self.ui
+            .tooltip("the position, speed and timing readout in the corner");
+        // only applied once it's let go so the menu doesn't resize under the mouse
+        let mut scale = settings.ui_scale;
+        self.ui
+            .slider("ui scale", &mut scale, MIN_UI_SCALE, MAX_UI_SCALE);
+        if !is_mouse_button_down(MouseButton::Left) {
+            settings.ui_scale = scale;
+        }
+        self.ui
+            .slider("zoom", &mut settings.zoom, camera.min_zoom, camera.max_zoom);
+        self.ui.tooltip("what worlds start zoomed to");
+        self.ui
+            .slider("camera smoothing", &mut settings.camera_smoothing, 0.0, 1.0);
+        let bindings = self.ui.button("key bindings");
+        let back = self.ui.button("back");
+        self.ui.end_panel("settings");
+
+        if bindings {
+            self.push(State::KeyBindings);
+        } else if back {
+            self.close_settings();
+        }
+    }
+
+    // two columns of buttons that each wait for a key when clicked
+    fn key_bindings_menu(&mut self) {
+        self.ui
+            .begin_panel("bindings", Anchor::Center, MENU_WIDTH * 2.0);
+        self.ui.heading("key bindings");
+        for pair in Action::ALL.chunks(2) {
+            self.ui.begin_row();
+            for action in pair {
+                let key = if self.rebinding == Some(*action) {
+                    "press a key".to_owned()
+                } else {
+                    key_name(self.settings.key(*action))
+                };
+                let label = format!("{:<12} {:>12}", action.name(), key);
+                if self.ui.button(&label) {
+                    self.rebinding = Some(*action);
+                }
+            }
+            self.ui.end_row();
+        }
+        let back = self.ui.button("back");
+        self.ui.end_panel("bindings");
+
+        if back {
+            self.close_settings();
+        }
+    }
+}
+
+fn shade_screen() {
+    let shade = Color::new(0.0, 0.0, 0.0, 0.6);
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), shade);
+}
+
+// numbers are used as they are, any other text is hashed so words work as seeds
+pub fn seed_from_text(text: &str) -> u32 {
+    let text = text.trim();
+    if text.is_empty() {
+        return date::now() as u32;
+    }
+    if let Ok(seed) = text.parse::<u32>() {
+        return seed;
+    }
+    // fnv-1a
+    let mut hash: u32 = 0x811c9dc5;
+    for byte in text.bytes() {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    return hash;
+}
+
+// save files in the working directory, by name
+fn find_saves() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(".") else {
+        return Vec::new();
+    };
+    let mut saves: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == SAVE_EXTENSION)
+        })
+        .filter_map(|path| path.file_name()?.to_str().map(|name| name.to_owned()))
+        .collect();
+    saves.sort();
+    return saves;
+}
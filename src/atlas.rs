@@ -0,0 +1,166 @@
+use lazy_static::lazy_static;
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+// generated by build.rs from every png in the atlas folders
+include!(concat!(env!("OUT_DIR"), "/atlas_images.rs"));
+
+// width and height of one atlas page in pixels
+const PAGE_SIZE: u32 = 1024;
+// empty pixels around every image so neighbours never bleed into each other
+const PADDING: u32 = 1;
+
+lazy_static! {
+    static ref ATLAS: TextureAtlas = TextureAtlas::load();
+}
+
+// the shared atlas with everything in the atlas folders, built on first use
+pub fn atlas() -> &'static TextureAtlas {
+    return &ATLAS;
+}
+
+// where an image ended up in the atlas
+#[derive(Clone, Copy, Debug)]
+pub struct AtlasRegion {
+    pub page: usize,
+    // in pixels on the page
+    pub rect: Rect,
+    // the same area in 0..1 texture coordinates
+    pub uv: Rect,
+}
+
+// images packed into as few textures as possible and looked up by name. names
+// are the path below src/assets without the extension, like `sprites/player`
+pub struct TextureAtlas {
+    pages: Vec<Texture2D>,
+    regions: HashMap<String, AtlasRegion>,
+}
+
+impl TextureAtlas {
+    pub fn load() -> Self {
+        let images: Vec<(String, Image)> = ATLAS_IMAGES
+            .iter()
+            .map(|(name, bytes)| (name.to_string(), decode(name, bytes)))
+            .collect();
+        return TextureAtlas::build(images);
+    }
+
+    // shelf packing: tallest images first, left to right in rows, and a new page
+    // whenever a row no longer fits
+    pub fn build(mut images: Vec<(String, Image)>) -> Self {
+        images.sort_by_key(|(name, image)| (std::cmp::Reverse(image.height), name.clone()));
+
+        let mut pages: Vec<Image> = Vec::new();
+        let mut regions: HashMap<String, AtlasRegion> = HashMap::new();
+        let (mut x, mut y, mut row_height) = (PAGE_SIZE, PAGE_SIZE, 0);
+
+        for (name, image) in &images {
+            let width = image.width as u32 + PADDING * 2;
+            let height = image.height as u32 + PADDING * 2;
+            if width > PAGE_SIZE || height > PAGE_SIZE {
+                panic!("{} is too big for a {}px atlas page", name, PAGE_SIZE);
+            }
+            if x + width > PAGE_SIZE {
+                x = 0;
+                y += row_height;
+                row_height = 0;
+            }
+            if y + height > PAGE_SIZE {
+                pages.push(Image::gen_image_color(
+                    PAGE_SIZE as u16,
+                    PAGE_SIZE as u16,
+                    BLANK,
+                ));
+                x = 0;
+                y = 0;
+                row_height = 0;
+            }
+
+            let page = pages.last_mut().unwrap();
+            for image_y in 0..image.height as u32 {
+                for image_x in 0..image.width as u32 {
+                    let color = image.get_pixel(image_x, image_y);
+                    page.set_pixel(x + PADDING + image_x, y + PADDING + image_y, color);
+                }
+            }
+
+            let rect = Rect::new(
+                (x + PADDING) as f32,
+                (y + PADDING) as f32,
+                image.width as f32,
+                image.height as f32,
+            );
+            let size = PAGE_SIZE as f32;
+            regions.insert(
+                name.clone(),
+                AtlasRegion {
+                    page: pages.len() - 1,
+                    rect,
+                    uv: Rect::new(rect.x / size, rect.y / size, rect.w / size, rect.h / size),
+                },
+            );
+            x += width;
+            row_height = row_height.max(height);
+        }
+
+        let pages = pages
+            .iter()
+            .map(|page| {
+                let texture = Texture2D::from_image(page);
+                texture.set_filter(FilterMode::Nearest);
+                texture
+            })
+            .collect();
+        return TextureAtlas { pages, regions };
+    }
+
+    pub fn get(&self, name: &str) -> Option<AtlasRegion> {
+        return self.regions.get(name).copied();
+    }
+
+    // like get, for images the game can't run without
+    pub fn region(&self, name: &str) -> AtlasRegion {
+        return self
+            .get(name)
+            .unwrap_or_else(|| panic!("`{}` is not in the texture atlas", name));
+    }
+
+    pub fn page(&self, index: usize) -> &Texture2D {
+        return &self.pages[index];
+    }
+
+    // draw_texture_ex for an atlas image, `params.source` is ignored
+    pub fn draw(&self, name: &str, x: f32, y: f32, color: Color, params: DrawTextureParams) {
+        let region = self.region(name);
+        draw_texture_ex(
+            self.page(region.page),
+            x,
+            y,
+            color,
+            DrawTextureParams {
+                source: Some(region.rect),
+                ..params
+            },
+        );
+    }
+}
+
+// checks a name without decoding anything or needing the atlas textures
+pub fn has_atlas_image(name: &str) -> bool {
+    return ATLAS_IMAGES
+        .iter()
+        .any(|(image_name, _)| *image_name == name);
+}
+
+// an atlas image decoded on its own, for drawing on the cpu without a window
+pub fn atlas_image(name: &str) -> Option<Image> {
+    return ATLAS_IMAGES
+        .iter()
+        .find(|(image_name, _)| *image_name == name)
+        .map(|(name, bytes)| decode(name, bytes));
+}
+
+fn decode(name: &str, bytes: &[u8]) -> Image {
+    return Image::from_file_with_format(bytes, Some(ImageFormat::Png))
+        .unwrap_or_else(|err| panic!("couldn't decode {}: {}", name, err));
+}
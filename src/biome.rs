@@ -0,0 +1,171 @@
+use crate::block::BlockType;
+use crate::utils::hex_color;
+use crate::weather::WeatherKind;
+use macroquad::prelude::*;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Biome {
+    Plains,
+    Desert,
+    Tundra,
+    Forest,
+    Mountains,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Feature {
+    None,
+    Tree,
+    Pine,
+    Cactus,
+}
+
+pub struct BiomeParams {
+    pub amplitude: f32,
+    // shifts the whole biome up (negative) or down (positive)
+    pub height_offset: f32,
+    pub surface_color: Color,
+    pub subsurface_color: Color,
+    pub stone_color: Color,
+    pub surface_type: BlockType,
+    pub subsurface_type: BlockType,
+    pub feature: Feature,
+    // chance per column to place the feature
+    pub feature_density: f32,
+    pub sky_top_color: Color,
+    pub sky_bottom_color: Color,
+    // what weather can happen here and how likely each one is
+    pub weather: &'static [(WeatherKind, f32)],
+}
+
+impl Biome {
+    pub const ALL: [Biome; 5] = [
+        Biome::Plains,
+        Biome::Desert,
+        Biome::Tundra,
+        Biome::Forest,
+        Biome::Mountains,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Biome> {
+        return Biome::ALL.into_iter().find(|biome| biome.name() == name);
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Biome::Plains => "plains",
+            Biome::Desert => "desert",
+            Biome::Tundra => "tundra",
+            Biome::Forest => "forest",
+            Biome::Mountains => "mountains",
+        }
+    }
+
+    pub fn params(&self) -> BiomeParams {
+        match self {
+            Biome::Plains => BiomeParams {
+                amplitude: 6.0,
+                height_offset: 0.0,
+                surface_color: hex_color("#1f3029", 1.0),
+                subsurface_color: hex_color("#2e2520", 1.0),
+                stone_color: hex_color("#2b2d33", 1.0),
+                surface_type: BlockType::Solid,
+                subsurface_type: BlockType::Solid,
+                feature: Feature::Tree,
+                feature_density: 0.04,
+                sky_top_color: hex_color("#0b0108", 1.0),
+                sky_bottom_color: hex_color("#1b1f27", 0.0),
+                weather: &[
+                    (WeatherKind::Clear, 0.5),
+                    (WeatherKind::Rain, 0.35),
+                    (WeatherKind::Storm, 0.15),
+                ],
+            },
+            Biome::Desert => BiomeParams {
+                amplitude: 8.0,
+                height_offset: 2.0,
+                surface_color: hex_color("#8c7a4f", 1.0),
+                subsurface_color: hex_color("#6b5a3a", 1.0),
+                stone_color: hex_color("#4a3f31", 1.0),
+                surface_type: BlockType::Sand,
+                subsurface_type: BlockType::Sand,
+                feature: Feature::Cactus,
+                feature_density: 0.05,
+                sky_top_color: hex_color("#1a0d05", 1.0),
+                sky_bottom_color: hex_color("#3a2a1c", 0.0),
+                weather: &[(WeatherKind::Clear, 0.9), (WeatherKind::Rain, 0.1)],
+            },
+            Biome::Tundra => BiomeParams {
+                amplitude: 7.0,
+                height_offset: 0.0,
+                surface_color: hex_color("#c9d3dc", 1.0),
+                subsurface_color: hex_color("#3a3f44", 1.0),
+                stone_color: hex_color("#2f3338", 1.0),
+                surface_type: BlockType::Solid,
+                subsurface_type: BlockType::Solid,
+                feature: Feature::Pine,
+                feature_density: 0.03,
+                sky_top_color: hex_color("#050a14", 1.0),
+                sky_bottom_color: hex_color("#27323f", 0.0),
+                weather: &[
+                    (WeatherKind::Clear, 0.3),
+                    (WeatherKind::Snow, 0.5),
+                    (WeatherKind::Blizzard, 0.2),
+                ],
+            },
+            Biome::Forest => BiomeParams {
+                amplitude: 10.0,
+                height_offset: -2.0,
+                surface_color: hex_color("#183a22", 1.0),
+                subsurface_color: hex_color("#2a201a", 1.0),
+                stone_color: hex_color("#282a2f", 1.0),
+                surface_type: BlockType::Solid,
+                subsurface_type: BlockType::Solid,
+                feature: Feature::Tree,
+                feature_density: 0.2,
+                sky_top_color: hex_color("#020805", 1.0),
+                sky_bottom_color: hex_color("#142319", 0.0),
+                weather: &[
+                    (WeatherKind::Clear, 0.4),
+                    (WeatherKind::Rain, 0.4),
+                    (WeatherKind::Storm, 0.2),
+                ],
+            },
+            Biome::Mountains => BiomeParams {
+                amplitude: 26.0,
+                height_offset: -12.0,
+                surface_color: hex_color("#5b5f66", 1.0),
+                subsurface_color: hex_color("#3b3e44", 1.0),
+                stone_color: hex_color("#26282d", 1.0),
+                surface_type: BlockType::Solid,
+                subsurface_type: BlockType::Gravel,
+                feature: Feature::Pine,
+                feature_density: 0.015,
+                sky_top_color: hex_color("#07070f", 1.0),
+                sky_bottom_color: hex_color("#222436", 0.0),
+                weather: &[
+                    (WeatherKind::Clear, 0.4),
+                    (WeatherKind::Snow, 0.35),
+                    (WeatherKind::Storm, 0.25),
+                ],
+            },
+        }
+    }
+}
+
+// the biome(s) at a column. near a border the neighbouring biome gets mixed in,
+// `blend` is how much of `secondary` to use (0 in the middle, 0.5 at the border)
+#[derive(Clone, Copy)]
+pub struct BiomeSample {
+    pub primary: Biome,
+    pub secondary: Biome,
+    pub blend: f32,
+}
+
+impl BiomeSample {
+    pub fn mix(&self, f: impl Fn(&BiomeParams) -> f32) -> f32 {
+        let a = f(&self.primary.params());
+        let b = f(&self.secondary.params());
+        return a + (b - a) * self.blend;
+    }
+}
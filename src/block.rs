@@ -1,5 +1,7 @@
+use crate::atlas::atlas;
 use crate::position::GridPos;
-use crate::{include_texture2d, BLOCK_SIZE};
+use crate::utils::hex_color;
+use crate::BLOCK_SIZE;
 use macroquad::prelude::*;
 
 #[derive(Clone, Copy)]
@@ -7,6 +9,8 @@ pub struct Block {
     pub block_type: BlockType,
     pub color: Color,
     pub overlay: BlockOverlay,
+    // how full the cell is, only liquids and snow ever go below 1
+    pub level: f32,
 }
 
 impl Block {
@@ -15,13 +19,115 @@ impl Block {
             block_type,
             color,
             overlay,
+            level: 1.0,
+        }
+    }
+
+    // light given off by the block, if any
+    pub fn emission(&self) -> Option<Color> {
+        match self.block_type {
+            BlockType::Torch => Some(hex_color("#ffb861", 1.0)),
+            BlockType::Lava => Some(hex_color("#ff6a1f", 1.0)),
+            // crystals glow in their own color
+            BlockType::Crystal => Some(self.color),
+            _ => None,
         }
     }
 }
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum BlockType {
     Solid,
+    Sand,
+    Gravel,
+    Water,
+    Lava,
+    Torch,
+    Crystal,
+    Snow,
+}
+
+impl BlockType {
+    pub const ALL: [BlockType; 8] = [
+        BlockType::Solid,
+        BlockType::Sand,
+        BlockType::Gravel,
+        BlockType::Water,
+        BlockType::Lava,
+        BlockType::Torch,
+        BlockType::Crystal,
+        BlockType::Snow,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlockType::Solid => "solid",
+            BlockType::Sand => "sand",
+            BlockType::Gravel => "gravel",
+            BlockType::Water => "water",
+            BlockType::Lava => "lava",
+            BlockType::Torch => "torch",
+            BlockType::Crystal => "crystal",
+            BlockType::Snow => "snow",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<BlockType> {
+        return BlockType::ALL.into_iter().find(|t| t.name() == name);
+    }
+
+    pub fn is_liquid(&self) -> bool {
+        return matches!(self, BlockType::Water | BlockType::Lava);
+    }
+
+    // whether the player collides with it, snow is soft enough to wade through
+    pub fn is_solid(&self) -> bool {
+        return !self.is_liquid() && !matches!(self, BlockType::Torch | BlockType::Snow);
+    }
+
+    // how much light is lost stepping into a cell of this type
+    pub fn light_cost(&self) -> u8 {
+        match self {
+            BlockType::Torch | BlockType::Snow => 1,
+            BlockType::Water | BlockType::Lava => 2,
+            _ => 3,
+        }
+    }
+
+    // liquids only flow every few ticks, lava is thick
+    pub fn flow_interval(&self) -> u32 {
+        match self {
+            BlockType::Lava => 4,
+            _ => 1,
+        }
+    }
+
+    pub fn opacity(&self) -> f32 {
+        match self {
+            BlockType::Water => 0.6,
+            BlockType::Lava => 0.9,
+            _ => 1.0,
+        }
+    }
+
+    // granular blocks fall when nothing is below them and slide off piles
+    pub fn is_granular(&self) -> bool {
+        return matches!(self, BlockType::Sand | BlockType::Gravel | BlockType::Snow);
+    }
+
+    // how many free cells a granular block needs below its diagonal before it
+    // slides, higher values make steeper piles
+    pub fn slide_drop(&self) -> i32 {
+        match self {
+            BlockType::Gravel => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn next(&self) -> BlockType {
+        let index = BlockType::ALL.iter().position(|t| t == self).unwrap();
+        return BlockType::ALL[(index + 1) % BlockType::ALL.len()];
+    }
 }
 
 #[derive(PartialEq, Clone, Copy)]
@@ -30,30 +136,67 @@ pub enum BlockOverlay {
     Top,
 }
 
-pub fn get_overlay_texture(overlay: BlockOverlay) -> Texture2D {
-    match overlay {
-        BlockOverlay::Top => include_texture2d!("./assets/overlays/top.png"),
-        _ => panic!("get_overlay_texture called on none overlay"),
+impl BlockOverlay {
+    pub const ALL: [BlockOverlay; 2] = [BlockOverlay::None, BlockOverlay::Top];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlockOverlay::None => "none",
+            BlockOverlay::Top => "top",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<BlockOverlay> {
+        return BlockOverlay::ALL.into_iter().find(|o| o.name() == name);
+    }
+
+    // the atlas image drawn over the block
+    pub fn texture_name(&self) -> Option<&'static str> {
+        match self {
+            BlockOverlay::None => None,
+            BlockOverlay::Top => Some("overlays/top"),
+        }
     }
 }
 
-pub fn render_block(block: Block, pos: GridPos) {
+// `light` is multiplied into the block color, pass WHITE for full brightness
+pub fn render_block(block: Block, pos: GridPos, light: Color) {
     let scaled_pos = pos * BLOCK_SIZE;
+    let mut color = block.color;
+    color.a *= block.block_type.opacity();
+    // emissive blocks are never darker than their own light
+    if block.emission().is_none() {
+        color.r *= light.r;
+        color.g *= light.g;
+        color.b *= light.b;
+    }
+    // partially filled cells are drawn from the bottom up
+    let height = BLOCK_SIZE * block.level;
+    // torches are a thin stick in the middle of the cell
+    let (x, width) = if block.block_type == BlockType::Torch {
+        (scaled_pos.x as f32 + BLOCK_SIZE / 3.0, BLOCK_SIZE / 3.0)
+    } else {
+        (scaled_pos.x as f32, BLOCK_SIZE)
+    };
     draw_rectangle(
-        scaled_pos.x as f32,
-        scaled_pos.y as f32,
-        BLOCK_SIZE,
-        BLOCK_SIZE,
-        block.color,
+        x,
+        scaled_pos.y as f32 + BLOCK_SIZE - height,
+        width,
+        height,
+        color,
     );
 }
 
-pub fn render_block_overlay(block: Block, pos: GridPos) {
+pub fn render_block_overlay(block: Block, pos: GridPos, light: Color) {
+    let Some(texture_name) = block.overlay.texture_name() else {
+        return;
+    };
     let scaled_pos = pos * BLOCK_SIZE;
-    draw_texture(
-        &get_overlay_texture(block.overlay),
+    atlas().draw(
+        texture_name,
         scaled_pos.x as f32,
         scaled_pos.y as f32,
-        Color::new(1.0, 1.0, 1.0, block.color.a),
+        Color::new(light.r, light.g, light.b, block.color.a),
+        DrawTextureParams::default(),
     )
 }
@@ -0,0 +1,217 @@
+use crate::light::SKY_LIMIT;
+use crate::position::SubGridPos;
+use crate::settings::key;
+use crate::settings::Action;
+use crate::terrain::WORLD_BOTTOM;
+use crate::vis::VisRange;
+use crate::BLOCK_SIZE;
+use macroquad::prelude::*;
+
+// how the camera follows the player, all distances are in blocks
+#[derive(Clone, Copy)]
+pub struct CameraSettings {
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    // how much one wheel notch zooms in or out
+    pub zoom_step: f32,
+    // fraction of the distance to the target covered each frame
+    pub follow_x: f32,
+    pub follow_y: f32,
+    // the player can move this far from the center before the camera follows
+    pub deadzone_x: f32,
+    pub deadzone_y: f32,
+    // how far ahead of the player to look per unit of horizontal velocity
+    pub look_ahead: f32,
+    // free camera speed with the pan keys, per frame
+    pub pan_speed: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        CameraSettings {
+            min_zoom: 2.0,
+            max_zoom: 16.0,
+            zoom_step: 0.1,
+            follow_x: 0.1,
+            follow_y: 0.02,
+            deadzone_x: 2.0,
+            deadzone_y: 3.0,
+            look_ahead: 30.0,
+            pan_speed: 1.5,
+        }
+    }
+}
+
+pub struct CameraController {
+    pub settings: CameraSettings,
+    pub zoom: f32,
+    // detached from the player, moved with middle-drag or the pan keys
+    pub free: bool,
+    // what the camera is centered on, in world pixels
+    pub center: Vec2,
+    // the camera never shows anything outside of this, in world pixels
+    pub bounds: VisRange,
+    look_ahead: f32,
+    // world position under the mouse when a middle-drag started
+    drag_anchor: Option<Vec2>,
+    camera: Camera2D,
+}
+
+impl CameraController {
+    pub fn new() -> Self {
+        CameraController {
+            settings: CameraSettings::default(),
+            zoom: 6.0,
+            free: false,
+            center: vec2(0.0, 0.0),
+            bounds: VisRange::new(
+                f32::NEG_INFINITY,
+                SKY_LIMIT as f32 * BLOCK_SIZE,
+                f32::INFINITY,
+                WORLD_BOTTOM as f32 * BLOCK_SIZE,
+            ),
+            look_ahead: 0.0,
+            drag_anchor: None,
+            camera: Camera2D {
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn camera(&self) -> &Camera2D {
+        return &self.camera;
+    }
+
+    // jump straight to the player without easing, after spawning or loading
+    pub fn snap_to(&mut self, player_pos: SubGridPos) {
+        self.center = player_center(player_pos);
+        self.look_ahead = 0.0;
+    }
+
+    // `zoom_locked` is set while something else is using the mouse wheel, and
+    // `input` is false while something else has the keyboard and mouse
+    pub fn update(
+        &mut self,
+        player_pos: SubGridPos,
+        player_vel_x: f32,
+        zoom_locked: bool,
+        input: bool,
+    ) {
+        if input {
+            self.input(zoom_locked);
+        }
+        if self.free {
+            if input {
+                self.pan();
+            }
+        } else {
+            self.follow(player_pos, player_vel_x);
+        }
+        self.clamp_to_bounds();
+        self.apply();
+    }
+
+    fn input(&mut self, zoom_locked: bool) {
+        if is_key_pressed(key(Action::FreeCamera)) {
+            self.free = !self.free;
+        }
+        let (_, wheel_y) = mouse_wheel();
+        if wheel_y != 0.0 && !zoom_locked {
+            let factor = 1.0 + self.settings.zoom_step * wheel_y.signum();
+            self.zoom = (self.zoom * factor).clamp(self.settings.min_zoom, self.settings.max_zoom);
+        }
+    }
+
+    fn pan(&mut self) {
+        let speed = self.settings.pan_speed * BLOCK_SIZE;
+        if is_key_down(key(Action::PanLeft)) {
+            self.center.x -= speed;
+        }
+        if is_key_down(key(Action::PanRight)) {
+            self.center.x += speed;
+        }
+        if is_key_down(key(Action::PanUp)) {
+            self.center.y -= speed;
+        }
+        if is_key_down(key(Action::PanDown)) {
+            self.center.y += speed;
+        }
+
+        // keep whatever was grabbed under the mouse
+        let mouse = self.camera.screen_to_world(mouse_position().into());
+        if is_mouse_button_down(MouseButton::Middle) {
+            match self.drag_anchor {
+                Some(anchor) => self.center += anchor - mouse,
+                None => self.drag_anchor = Some(mouse),
+            }
+        } else {
+            self.drag_anchor = None;
+        }
+    }
+
+    fn follow(&mut self, player_pos: SubGridPos, player_vel_x: f32) {
+        self.drag_anchor = None;
+        let settings = self.settings;
+        self.look_ahead +=
+            (player_vel_x * settings.look_ahead - self.look_ahead) * settings.follow_x;
+        let target = player_center(player_pos) + vec2(self.look_ahead * BLOCK_SIZE, 0.0);
+
+        // only move once the target has left the deadzone, and only as far as its edge
+        let deadzone = vec2(settings.deadzone_x, settings.deadzone_y) * BLOCK_SIZE;
+        let offset = target - self.center;
+        let outside = vec2(
+            offset.x.signum() * (offset.x.abs() - deadzone.x).max(0.0),
+            offset.y.signum() * (offset.y.abs() - deadzone.y).max(0.0),
+        );
+        self.center.x += outside.x * settings.follow_x;
+        self.center.y += outside.y * settings.follow_y;
+    }
+
+    // half the visible area in world pixels
+    fn half_extent(&self) -> Vec2 {
+        return vec2(screen_width(), screen_height()) / self.zoom;
+    }
+
+    fn clamp_to_bounds(&mut self) {
+        let half = self.half_extent();
+        let clamp_axis = |center: f32, min: f32, max: f32, half: f32| {
+            // a view bigger than the bounds stays in the middle
+            if max - min <= half * 2.0 {
+                return (min + max) / 2.0;
+            }
+            return center.clamp(min + half, max - half);
+        };
+        if self.bounds.min_x.is_finite() && self.bounds.max_x.is_finite() {
+            self.center.x = clamp_axis(self.center.x, self.bounds.min_x, self.bounds.max_x, half.x);
+        }
+        self.center.y = clamp_axis(self.center.y, self.bounds.min_y, self.bounds.max_y, half.y);
+    }
+
+    fn apply(&mut self) {
+        self.camera.zoom = vec2(
+            self.zoom / screen_width(),
+            (screen_width() / screen_height() * self.zoom) / screen_width(),
+        );
+        self.camera.target = self.center;
+        set_camera(&self.camera);
+    }
+
+    // what's on screen in world pixels, padded by a block on every side
+    pub fn visible(&self) -> VisRange {
+        let min = self.camera.screen_to_world(Vec2::new(0.0, 0.0));
+        let max = self
+            .camera
+            .screen_to_world(Vec2::new(screen_width(), screen_height()));
+        // adding padding to avoid visible culling near screen sides
+        return VisRange::new(
+            min.x.min(max.x) - BLOCK_SIZE,
+            min.y.min(max.y) - BLOCK_SIZE,
+            min.x.max(max.x) + BLOCK_SIZE,
+            min.y.max(max.y) + BLOCK_SIZE,
+        );
+    }
+}
+
+fn player_center(player_pos: SubGridPos) -> Vec2 {
+    return vec2(player_pos.x + 1.5, player_pos.y + 1.5) * BLOCK_SIZE;
+}
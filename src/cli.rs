@@ -0,0 +1,396 @@
+use crate::app::seed_from_text;
+use crate::block::BlockOverlay;
+use crate::block::BlockType;
+use crate::clock::START_TIME;
+use crate::export::render_blocks;
+use crate::export::save_png;
+use crate::export::GridRect;
+use crate::game::EXPORT_SCALE;
+use crate::import::image_blocks;
+use crate::import::load_png;
+use crate::position::GridPos;
+use crate::position::SubGridPos;
+use crate::save::WorldFile;
+use crate::settings::MAX_WINDOW;
+use crate::settings::MIN_WINDOW;
+use crate::terrain::chunk_of;
+use crate::tiled::TiledMap;
+use crate::utils::hex_color;
+use crate::world::BG_COLOR;
+use crate::BUILD_VERSION;
+use std::path::Path;
+
+pub const USAGE: &str = "usage:
+  vexalab [world.vxw] [options]         play, opening a world if one is given
+    --open <file>                       open a saved world
+    --seed <seed>                       start a new world, numbers are used as they
+                                        are and anything else is hashed
+    --debug                             start with the debug text and the
+                                        collision, chunk and entity overlays on
+    --size <width>x<height>             window size for this run
+
+  vexalab render-world <file> <out.png> [--scale <n>] [--area <x1,y1,x2,y2>]
+                                        draw a world's blocks into an image
+  vexalab convert <file> <out> [--seed <seed>] [--quantize]
+                                        convert between .vxw, .tmx/.tmj, and .png
+                                        (.tmx is read only, .png written is a render)
+  vexalab info <file>                   print block counts and bounds
+  vexalab help | version
+
+worlds can be .vxw saves, Tiled .tmx/.tmj maps or .png images, one block per pixel";
+
+// flags in the order they were given, with their values
+type Flags = Vec<(String, Option<String>)>;
+
+// how the game itself should start
+#[derive(Default)]
+pub struct LaunchOptions {
+    pub open: Option<String>,
+    pub seed: Option<u32>,
+    pub debug: bool,
+    pub window: Option<(u32, u32)>,
+}
+
+// what the command line asked for. everything but Play runs without a window
+pub enum Command {
+    Play(LaunchOptions),
+    RenderWorld {
+        input: String,
+        output: String,
+        scale: u32,
+        area: Option<GridRect>,
+    },
+    Convert {
+        input: String,
+        output: String,
+        seed: u32,
+        quantize: bool,
+    },
+    Info {
+        input: String,
+    },
+    Help,
+    Version,
+}
+
+// `args` without the program name
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    let Some(first) = args.first() else {
+        return Ok(Command::Play(LaunchOptions::default()));
+    };
+    let rest = &args[1..];
+    return match first.as_str() {
+        "render-world" => parse_render(rest),
+        "convert" => parse_convert(rest),
+        "info" => {
+            let (files, flags) = split_args(rest, &[])?;
+            if let Some((flag, _)) = flags.first() {
+                return Err(format!("unknown option `{}`", flag));
+            }
+            let [input] = expect_files::<1>(files, "info needs a world file")?;
+            Ok(Command::Info { input })
+        }
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        "version" | "--version" | "-V" => Ok(Command::Version),
+        _ => parse_play(args),
+    };
+}
+
+fn parse_play(args: &[String]) -> Result<Command, String> {
+    let (files, flags) = split_args(args, &["--open", "--seed", "--size"])?;
+    let mut options = LaunchOptions::default();
+    match files.len() {
+        0 => {}
+        1 => options.open = files.into_iter().next(),
+        _ => return Err("only one world can be opened".to_owned()),
+    }
+    for (flag, value) in flags {
+        match flag.as_str() {
+            "--open" => {
+                if options.open.is_some() {
+                    return Err("only one world can be opened".to_owned());
+                }
+                options.open = value;
+            }
+            "--seed" => options.seed = value.as_deref().map(seed_from_text),
+            "--size" => {
+                let value = value.ok_or("--size needs a value")?;
+                options.window = Some(parse_size(&value)?);
+            }
+            "--debug" => options.debug = true,
+            _ => return Err(format!("unknown option `{}`", flag)),
+        }
+    }
+    if options.open.is_some() && options.seed.is_some() {
+        return Err("--seed makes a new world, it can't be used when opening one".to_owned());
+    }
+    return Ok(Command::Play(options));
+}
+
+fn parse_render(args: &[String]) -> Result<Command, String> {
+    let (files, flags) = split_args(args, &["--scale", "--area"])?;
+    let [input, output] =
+        expect_files::<2>(files, "render-world needs a world file and an output image")?;
+    let mut scale = EXPORT_SCALE;
+    let mut area = None;
+    for (flag, value) in flags {
+        let value = value.unwrap_or_default();
+        match flag.as_str() {
+            "--scale" => {
+                scale = value
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|scale| *scale > 0)
+                    .ok_or(format!("invalid scale `{}`", value))?
+            }
+            "--area" => area = Some(parse_area(&value)?),
+            _ => return Err(format!("unknown option `{}`", flag)),
+        }
+    }
+    return Ok(Command::RenderWorld {
+        input,
+        output,
+        scale,
+        area,
+    });
+}
+
+fn parse_convert(args: &[String]) -> Result<Command, String> {
+    let (files, flags) = split_args(args, &["--seed"])?;
+    let [input, output] = expect_files::<2>(files, "convert needs an input and an output file")?;
+    let mut seed = 0;
+    let mut quantize = false;
+    for (flag, value) in flags {
+        match flag.as_str() {
+            "--seed" => seed = seed_from_text(&value.unwrap_or_default()),
+            "--quantize" => quantize = true,
+            _ => return Err(format!("unknown option `{}`", flag)),
+        }
+    }
+    return Ok(Command::Convert {
+        input,
+        output,
+        seed,
+        quantize,
+    });
+}
+
+// splits into plain arguments and flags, taking the next argument as the value
+// for the flags in `with_value`. anything starting with a dash is a flag, so a
+// mistyped `-x` is reported rather than opened as a file
+fn split_args(args: &[String], with_value: &[&str]) -> Result<(Vec<String>, Flags), String> {
+    let mut files: Vec<String> = Vec::new();
+    let mut flags: Flags = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') {
+            files.push(arg.clone());
+            continue;
+        }
+        let value = if with_value.contains(&arg.as_str()) {
+            Some(args.next().ok_or(format!("{} needs a value", arg))?.clone())
+        } else {
+            None
+        };
+        flags.push((arg.clone(), value));
+    }
+    return Ok((files, flags));
+}
+
+fn expect_files<const N: usize>(files: Vec<String>, message: &str) -> Result<[String; N], String> {
+    return files.try_into().map_err(|_| message.to_owned());
+}
+
+// `1280x720`
+fn parse_size(text: &str) -> Result<(u32, u32), String> {
+    let invalid = || {
+        format!(
+            "invalid window size `{}`, expected something like 1280x720",
+            text
+        )
+    };
+    let (width, height) = text.split_once('x').ok_or_else(invalid)?;
+    let width: u32 = width.parse().map_err(|_| invalid())?;
+    let height: u32 = height.parse().map_err(|_| invalid())?;
+    if width < MIN_WINDOW.0
+        || height < MIN_WINDOW.1
+        || width > MAX_WINDOW.0
+        || height > MAX_WINDOW.1
+    {
+        return Err(format!(
+            "window size {}x{} is outside {}x{} to {}x{}",
+            width, height, MIN_WINDOW.0, MIN_WINDOW.1, MAX_WINDOW.0, MAX_WINDOW.1
+        ));
+    }
+    return Ok((width, height));
+}
+
+// `x1,y1,x2,y2` in blocks, both corners included
+fn parse_area(text: &str) -> Result<GridRect, String> {
+    let values: Vec<i32> = text
+        .split(',')
+        .map(|value| value.trim().parse::<i32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid area `{}`", text))?;
+    let [x1, y1, x2, y2] = values[..] else {
+        return Err(format!("invalid area `{}`, expected x1,y1,x2,y2", text));
+    };
+    return Ok(GridRect::new(
+        GridPos::new(x1, y1, false),
+        GridPos::new(x2, y2, false),
+    ));
+}
+
+// runs one of the commands that don't need a window
+pub fn run_tool(command: Command) -> Result<(), String> {
+    match command {
+        Command::Play(_) => return Err("playing needs a window".to_owned()),
+        Command::RenderWorld {
+            input,
+            output,
+            scale,
+            area,
+        } => {
+            let file = read_any(&input, 0, false)?;
+            let area = match area {
+                Some(area) => area,
+                None => GridRect::around(&file.blocks).ok_or(format!("{} has no blocks", input))?,
+            };
+            render_file(&file, area, scale, &output)?;
+            println!(
+                "rendered {}x{} blocks of {} to {}",
+                area.width(),
+                area.height(),
+                input,
+                output
+            );
+        }
+        Command::Convert {
+            input,
+            output,
+            seed,
+            quantize,
+        } => {
+            let file = read_any(&input, seed, quantize)?;
+            match extension(&output).as_str() {
+                "vxw" => file.write(&output)?,
+                "tmj" | "json" => TiledMap::from_blocks(&file.blocks, file.player)
+                    .ok_or(format!("{} has no blocks", input))?
+                    .write_tmj(&output)?,
+                "png" => {
+                    let area =
+                        GridRect::around(&file.blocks).ok_or(format!("{} has no blocks", input))?;
+                    render_file(&file, area, EXPORT_SCALE, &output)?;
+                }
+                _ => return Err(format!("can't write {}, use .vxw, .tmj or .png", output)),
+            }
+            println!(
+                "converted {} to {} ({} blocks)",
+                input,
+                output,
+                file.blocks.len()
+            );
+        }
+        Command::Info { input } => {
+            let file = read_any(&input, 0, false)?;
+            print!("{}", info(&input, &file));
+        }
+        Command::Help => println!("{}", USAGE),
+        Command::Version => println!("vexalab {}", BUILD_VERSION),
+    }
+    return Ok(());
+}
+
+fn extension(path: &str) -> String {
+    return Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+}
+
+// any world the game can open or import, as a world file. `seed` is used for
+// formats that don't have one, `quantize` snaps image colors to the palette
+fn read_any(path: &str, seed: u32, quantize: bool) -> Result<WorldFile, String> {
+    return match extension(path).as_str() {
+        "vxw" => WorldFile::read(path),
+        "tmx" | "tmj" | "json" => Ok(TiledMap::read(path)?.into_world_file(seed)),
+        "png" => {
+            let image = load_png(path)?;
+            let blocks = image_blocks(&image, GridPos::new(0, 0, false), quantize);
+            let last = chunk_of(image.width as i32 - 1);
+            Ok(WorldFile {
+                seed,
+                time: START_TIME,
+                // standing on the top left corner
+                player: SubGridPos { x: 0.0, y: -3.0 },
+                chunks: (0..=last).collect(),
+                blocks: blocks.into_iter().collect(),
+            })
+        }
+        _ => Err(format!("can't read {}, use .vxw, .tmx, .tmj or .png", path)),
+    };
+}
+
+fn render_file(file: &WorldFile, area: GridRect, scale: u32, path: &str) -> Result<(), String> {
+    let image = render_blocks(&file.blocks, area, scale, hex_color(BG_COLOR, 1.0))?;
+    return save_png(&image, path);
+}
+
+fn info(path: &str, file: &WorldFile) -> String {
+    let mut lines = vec![
+        path.to_owned(),
+        format!("seed: {}", file.seed),
+        format!("time: {}", file.time),
+        format!("player: {}, {}", file.player.x, file.player.y),
+    ];
+    match (file.chunks.iter().min(), file.chunks.iter().max()) {
+        (Some(first), Some(last)) => lines.push(format!(
+            "chunks: {} generated, {} to {}",
+            file.chunks.len(),
+            first,
+            last
+        )),
+        _ => lines.push("chunks: none generated".to_owned()),
+    }
+
+    let background = file.blocks.keys().filter(|pos| pos.bg).count();
+    lines.push(format!(
+        "blocks: {} ({} foreground, {} background)",
+        file.blocks.len(),
+        file.blocks.len() - background,
+        background
+    ));
+    match GridRect::around(&file.blocks) {
+        Some(area) => lines.push(format!(
+            "bounds: x {} to {}, y {} to {} ({}x{})",
+            area.min_x,
+            area.max_x,
+            area.min_y,
+            area.max_y,
+            area.width(),
+            area.height()
+        )),
+        None => lines.push("bounds: none".to_owned()),
+    }
+    for block_type in BlockType::ALL {
+        let count = file
+            .blocks
+            .values()
+            .filter(|block| block.block_type == block_type)
+            .count();
+        if count > 0 {
+            lines.push(format!("  {}: {}", block_type.name(), count));
+        }
+    }
+    for overlay in BlockOverlay::ALL {
+        let count = file
+            .blocks
+            .values()
+            .filter(|block| block.overlay == overlay)
+            .count();
+        if overlay != BlockOverlay::None && count > 0 {
+            lines.push(format!("  {} overlay: {}", overlay.name(), count));
+        }
+    }
+    return lines.join("\n") + "\n";
+}
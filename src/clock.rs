@@ -0,0 +1,135 @@
+use crate::utils::hex_color;
+use crate::utils::lerp_color;
+use macroquad::prelude::*;
+use std::f32::consts::PI;
+
+// real seconds for a full day
+pub const DEFAULT_DAY_LENGTH: f32 = 600.0;
+// new worlds start in the morning
+pub const START_TIME: f32 = 0.3;
+
+// what the sky looks like at a point in the day. at full daylight the biome's own
+// sky colors are used, the keyframe colors take over as daylight fades
+struct SkyKeyframe {
+    time: f32,
+    top: Color,
+    bottom: Color,
+    daylight: f32,
+    ambient: Color,
+}
+
+fn night(time: f32) -> SkyKeyframe {
+    SkyKeyframe {
+        time,
+        top: hex_color("#010106", 1.0),
+        bottom: hex_color("#0a0d1c", 0.0),
+        daylight: 0.0,
+        ambient: Color::new(0.22, 0.25, 0.4, 1.0),
+    }
+}
+
+fn keyframes() -> [SkyKeyframe; 5] {
+    let dawn = SkyKeyframe {
+        time: 0.25,
+        top: hex_color("#1c1030", 1.0),
+        bottom: hex_color("#d9764a", 0.6),
+        daylight: 0.4,
+        ambient: Color::new(0.85, 0.65, 0.55, 1.0),
+    };
+    let noon = SkyKeyframe {
+        time: 0.5,
+        top: hex_color("#0b0108", 1.0),
+        bottom: hex_color("#1b1f27", 0.0),
+        daylight: 1.0,
+        ambient: WHITE,
+    };
+    let dusk = SkyKeyframe {
+        time: 0.75,
+        top: hex_color("#150b26", 1.0),
+        bottom: hex_color("#c4523b", 0.6),
+        daylight: 0.4,
+        ambient: Color::new(0.8, 0.55, 0.5, 1.0),
+    };
+    return [night(0.0), dawn, noon, dusk, night(1.0)];
+}
+
+pub struct WorldClock {
+    // 0 is midnight, 0.5 is noon
+    pub time: f32,
+    pub day_length: f32,
+}
+
+impl WorldClock {
+    pub fn new(time: f32, day_length: f32) -> Self {
+        WorldClock { time, day_length }
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        self.time = (self.time + delta / self.day_length).rem_euclid(1.0);
+    }
+
+    // the two keyframes around the current time and how far between them we are.
+    // times outside the day wrap around into it
+    fn surrounding(&self) -> (SkyKeyframe, SkyKeyframe, f32) {
+        let time = if self.time.is_finite() {
+            self.time.rem_euclid(1.0)
+        } else {
+            0.0
+        };
+        let frames = keyframes();
+        let next = frames
+            .iter()
+            .position(|frame| frame.time > time)
+            .unwrap_or(frames.len() - 1)
+            .max(1);
+        let mut frames = frames.into_iter().skip(next - 1);
+        let before = frames.next().unwrap();
+        let after = frames.next().unwrap();
+        let t = (time - before.time) / (after.time - before.time);
+        return (before, after, t);
+    }
+
+    // 0 at night, 1 at noon
+    pub fn daylight(&self) -> f32 {
+        let (before, after, t) = self.surrounding();
+        return before.daylight + (after.daylight - before.daylight) * t;
+    }
+
+    // multiplied into sky light when drawing blocks
+    pub fn ambient(&self) -> Color {
+        let (before, after, t) = self.surrounding();
+        return lerp_color(before.ambient, after.ambient, t);
+    }
+
+    // mixes the biome's daytime sky with the colors for this time of day
+    pub fn sky_colors(&self, biome_top: Color, biome_bottom: Color) -> (Color, Color) {
+        let (before, after, t) = self.surrounding();
+        let daylight = self.daylight();
+        let top = lerp_color(before.top, after.top, t);
+        let bottom = lerp_color(before.bottom, after.bottom, t);
+        return (
+            lerp_color(top, biome_top, daylight),
+            lerp_color(bottom, biome_bottom, daylight),
+        );
+    }
+
+    // position on screen (0..1, y up) of a body that rises at `rise` and sets half a day later
+    fn arc(&self, rise: f32) -> Vec2 {
+        let angle = (self.time - rise).rem_euclid(1.0) * 2.0 * PI;
+        return vec2(0.5 - 0.45 * angle.cos(), 0.25 + 0.6 * angle.sin());
+    }
+
+    pub fn sun_pos(&self) -> Vec2 {
+        return self.arc(0.25);
+    }
+
+    pub fn moon_pos(&self) -> Vec2 {
+        return self.arc(0.75);
+    }
+
+    // hours and minutes for showing the time
+    pub fn clock_time(&self) -> (u32, u32) {
+        let minutes = (self.time * 24.0 * 60.0) as u32;
+        return (minutes / 60, minutes % 60);
+    }
+}
@@ -0,0 +1,759 @@
+use crate::block::Block;
+use crate::block::BlockOverlay;
+use crate::block::BlockType;
+use crate::export::image_size;
+use crate::export::render_blocks;
+use crate::export::save_png;
+use crate::export::GridRect;
+use crate::game::Game;
+use crate::game::EXPORT_SCALE;
+use crate::game::QUICKSAVE_PATH;
+use crate::import::image_blocks;
+use crate::import::load_png;
+use crate::player::GameMode;
+use crate::position::GridPos;
+use crate::position::SubGridPos;
+use crate::profiler::is_recording;
+use crate::profiler::save_recording;
+use crate::profiler::start_recording;
+use crate::save::load_world;
+use crate::save::save_world;
+use crate::settings::key;
+use crate::settings::Action;
+use crate::tiled::export_map;
+use crate::tiled::import_map;
+use crate::utils::draw_text_ex_shadow;
+use crate::utils::hex_color;
+use crate::utils::is_hex_color;
+use crate::weather::WeatherKind;
+use crate::world::inside_world;
+use macroquad::prelude::*;
+
+// lines of output kept around, and how many of the newest are shown
+const MAX_LOG: usize = 200;
+const VISIBLE_LINES: usize = 14;
+const FONT_SIZE: u16 = 16;
+const LINE_HEIGHT: f32 = 18.0;
+// while closed, lines printed this many seconds ago or less show in the bottom
+// left, fading out over the last second
+const NOTICE_SECONDS: f64 = 4.0;
+const MAX_NOTICES: usize = 4;
+// seconds a key is held before it starts repeating, and between repeats
+const REPEAT_DELAY: f32 = 0.4;
+const REPEAT_INTERVAL: f32 = 0.04;
+// fill refuses to touch more cells than this at once
+const MAX_FILL: i64 = 100_000;
+// where `profile stop` writes without a path
+const PROFILE_PATH: &str = "profile.json";
+
+pub struct Command {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub help: &'static str,
+    // what tab offers for the arguments
+    pub words: fn() -> Vec<&'static str>,
+    // returns what to print
+    pub run: fn(&mut Game, &[&str]) -> Result<String, String>,
+}
+
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "help",
+        usage: "help [command]",
+        help: "lists commands, or explains one",
+        words: command_names,
+        run: help,
+    },
+    Command {
+        name: "tp",
+        usage: "tp <x> <y>",
+        help: "moves the player to a cell",
+        words: no_words,
+        run: teleport,
+    },
+    Command {
+        name: "give",
+        usage: "give <type> [#color]",
+        help: "puts a block type, and maybe a color, in the placer",
+        words: block_names,
+        run: give,
+    },
+    Command {
+        name: "setblock",
+        usage: "setblock <x> <y> <type|air> [#color] [bg]",
+        help: "places or removes one block, in the placer's color by default",
+        words: block_words,
+        run: set_block,
+    },
+    Command {
+        name: "fill",
+        usage: "fill <x1> <y1> <x2> <y2> <#color|air> [type] [bg]",
+        help: "fills a rectangle of cells, both corners included",
+        words: block_words,
+        run: fill,
+    },
+    Command {
+        name: "time",
+        usage: "time [set <hh:mm|day|noon|night|midnight|0-1>]",
+        help: "shows or changes the time of day",
+        words: time_words,
+        run: time,
+    },
+    Command {
+        name: "gamemode",
+        usage: "gamemode [survival|creative]",
+        help: "shows or changes the game mode, creative flies and takes no damage",
+        words: mode_names,
+        run: game_mode,
+    },
+    Command {
+        name: "weather",
+        usage: "weather [kind]",
+        help: "shows or changes the weather",
+        words: weather_names,
+        run: weather,
+    },
+    Command {
+        name: "seed",
+        usage: "seed",
+        help: "shows the world seed",
+        words: no_words,
+        run: seed,
+    },
+    Command {
+        name: "save",
+        usage: "save [path]",
+        help: "saves the world, to the quicksave by default",
+        words: no_words,
+        run: save,
+    },
+    Command {
+        name: "load",
+        usage: "load [path]",
+        help: "loads a world, the quicksave by default",
+        words: no_words,
+        run: load,
+    },
+    Command {
+        name: "import",
+        usage: "import <png> <x> <y> [bg] [snap]",
+        help: "places an image as blocks, snap limits it to the placer's colors",
+        words: import_words,
+        run: import,
+    },
+    Command {
+        name: "export",
+        usage: "export <png> [scale] [x1 y1 x2 y2]",
+        help: "renders every block, or a rectangle, into an image",
+        words: no_words,
+        run: export,
+    },
+    Command {
+        name: "map",
+        usage: "map <import|export> <path> [x y]",
+        help: "imports a Tiled map, at x y if it has no origin, or exports one",
+        words: map_words,
+        run: map,
+    },
+    Command {
+        name: "profile",
+        usage: "profile <start|stop> [path]",
+        help: "records frame timings, stop writes a .csv or a Chrome trace",
+        words: profile_words,
+        run: profile,
+    },
+];
+
+struct LogLine {
+    text: String,
+    error: bool,
+    // get_time() when it was printed
+    printed: f64,
+}
+
+// the ` key opens a line to type commands into. the world is paused while it's
+// open so typing doesn't also move the player
+pub struct Console {
+    pub open: bool,
+    input: String,
+    history: Vec<String>,
+    // how far back the up arrow went, None while typing something new
+    browsing: Option<usize>,
+    log: Vec<LogLine>,
+    // key being held down and seconds until it repeats
+    held: Option<(KeyCode, f32)>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Console {
+            open: false,
+            input: String::new(),
+            history: Vec::new(),
+            browsing: None,
+            log: Vec::new(),
+            held: None,
+        }
+    }
+
+    pub fn print(&mut self, text: &str, error: bool) {
+        for line in text.lines() {
+            self.log.push(LogLine {
+                text: line.to_owned(),
+                error,
+                printed: get_time(),
+            });
+        }
+        if self.log.len() > MAX_LOG {
+            self.log.drain(..self.log.len() - MAX_LOG);
+        }
+    }
+
+    // returns a line once enter is pressed on it
+    pub fn update(&mut self) -> Option<String> {
+        if is_key_pressed(key(Action::Console)) {
+            self.open = !self.open;
+            self.held = None;
+        }
+        // typed characters queue up even while nothing reads them
+        let typed: Vec<char> = std::iter::from_fn(get_char_pressed).collect();
+        if !self.open {
+            return None;
+        }
+
+        // whatever the console key types, including its repeats while held
+        let console_key = is_key_down(key(Action::Console));
+        for character in typed {
+            if !character.is_control() && !console_key {
+                self.input.push(character);
+                self.browsing = None;
+            }
+        }
+        if self.repeated(KeyCode::Backspace) {
+            self.input.pop();
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            self.open = false;
+        }
+        if is_key_pressed(KeyCode::Tab) {
+            self.complete();
+        }
+        if is_key_pressed(KeyCode::Up) {
+            self.browse(1);
+        }
+        if is_key_pressed(KeyCode::Down) {
+            self.browse(-1);
+        }
+
+        if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+            let line = self.input.trim().to_owned();
+            self.input.clear();
+            self.browsing = None;
+            if line.is_empty() {
+                return None;
+            }
+            if self.history.last() != Some(&line) {
+                self.history.push(line.clone());
+            }
+            self.print(&format!("> {}", line), false);
+            return Some(line);
+        }
+        return None;
+    }
+
+    // pressed this frame, or held long enough to repeat
+    fn repeated(&mut self, key: KeyCode) -> bool {
+        if is_key_pressed(key) {
+            self.held = Some((key, REPEAT_DELAY));
+            return true;
+        }
+        let Some((held_key, wait)) = self.held else {
+            return false;
+        };
+        if held_key != key {
+            return false;
+        }
+        if !is_key_down(key) {
+            self.held = None;
+            return false;
+        }
+        let wait = wait - get_frame_time();
+        if wait <= 0.0 {
+            self.held = Some((key, REPEAT_INTERVAL));
+            return true;
+        }
+        self.held = Some((key, wait));
+        return false;
+    }
+
+    // `steps` back through the history, negative goes forward again
+    fn browse(&mut self, steps: i32) {
+        if self.history.is_empty() {
+            return;
+        }
+        let back = match self.browsing {
+            Some(back) => back as i32 + steps,
+            None if steps > 0 => 0,
+            None => return,
+        };
+        if back < 0 {
+            self.browsing = None;
+            self.input.clear();
+            return;
+        }
+        let back = (back as usize).min(self.history.len() - 1);
+        self.browsing = Some(back);
+        self.input = self.history[self.history.len() - 1 - back].clone();
+    }
+
+    // completes the last word as far as it's unambiguous and lists the options
+    fn complete(&mut self) {
+        // only spaces would end up in front of the command
+        if self.input.trim().is_empty() {
+            self.input.clear();
+        }
+        let words: Vec<&str> = self.input.split_whitespace().collect();
+        let starting_new = self.input.is_empty() || self.input.ends_with(' ');
+        let partial = if starting_new {
+            ""
+        } else {
+            words.last().unwrap()
+        };
+
+        // with nothing typed yet every command fits
+        let options: Vec<&'static str> = if words.is_empty() || (words.len() == 1 && !starting_new)
+        {
+            command_names()
+        } else {
+            match COMMANDS.iter().find(|command| command.name == words[0]) {
+                Some(command) => (command.words)(),
+                None => return,
+            }
+        };
+        let matches: Vec<&str> = options
+            .into_iter()
+            .filter(|option| option.starts_with(partial))
+            .collect();
+        if matches.is_empty() {
+            return;
+        }
+
+        let mut common = matches[0].to_owned();
+        for other in &matches[1..] {
+            while !other.starts_with(common.as_str()) {
+                common.pop();
+            }
+        }
+        self.input.truncate(self.input.len() - partial.len());
+        self.input.push_str(&common);
+        if matches.len() == 1 {
+            self.input.push(' ');
+        } else {
+            self.print(&matches.join("  "), false);
+        }
+    }
+
+    // over the top of the screen, with the default camera set
+    pub fn render(&self, font: &Font) {
+        if !self.open {
+            self.render_notices(font);
+            return;
+        }
+        let height = (VISIBLE_LINES + 1) as f32 * LINE_HEIGHT + 8.0;
+        draw_rectangle(
+            0.0,
+            0.0,
+            screen_width(),
+            height,
+            Color::new(0.0, 0.0, 0.0, 0.8),
+        );
+
+        let params = |color: Color| TextParams {
+            font: Some(font),
+            font_size: FONT_SIZE,
+            color,
+            ..Default::default()
+        };
+        let first = self.log.len().saturating_sub(VISIBLE_LINES);
+        for (index, line) in self.log[first..].iter().enumerate() {
+            let y = LINE_HEIGHT * (index + 1) as f32;
+            let color = line.color();
+            draw_text_ex_shadow(&line.text, 8.0, y, params(color));
+        }
+
+        // the cursor blinks twice a second
+        let cursor = if get_time().fract() < 0.5 { "_" } else { "" };
+        let prompt = format!("> {}{}", self.input, cursor);
+        draw_text_ex_shadow(&prompt, 8.0, height - 8.0, params(WHITE));
+    }
+
+    // the last few lines, so what F-keys and commands report is seen without
+    // opening the console
+    fn render_notices(&self, font: &Font) {
+        let now = get_time();
+        let recent: Vec<&LogLine> = self
+            .log
+            .iter()
+            .rev()
+            .take(MAX_NOTICES)
+            .take_while(|line| now - line.printed < NOTICE_SECONDS)
+            .collect();
+        for (index, line) in recent.iter().enumerate() {
+            let mut color = line.color();
+            color.a = (NOTICE_SECONDS - (now - line.printed)).min(1.0) as f32;
+            let y = screen_height() - 8.0 - LINE_HEIGHT * index as f32;
+            draw_text_ex_shadow(
+                &line.text,
+                8.0,
+                y,
+                TextParams {
+                    font: Some(font),
+                    font_size: FONT_SIZE,
+                    color,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
+
+impl LogLine {
+    fn color(&self) -> Color {
+        if self.error {
+            return Color::new(1.0, 0.45, 0.4, 1.0);
+        }
+        return LIGHTGRAY;
+    }
+}
+
+pub fn run_command(game: &mut Game, line: &str) -> Result<String, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let Some((name, args)) = words.split_first() else {
+        return Ok(String::new());
+    };
+    let command = COMMANDS
+        .iter()
+        .find(|command| command.name == *name)
+        .ok_or(format!("unknown command `{}`, try help", name))?;
+    return (command.run)(game, args).map_err(|err| format!("{}\nusage: {}", err, command.usage));
+}
+
+fn number<T: std::str::FromStr>(args: &[&str], index: usize, what: &str) -> Result<T, String> {
+    let value = args.get(index).ok_or(format!("missing {}", what))?;
+    return value
+        .parse()
+        .map_err(|_| format!("invalid {} `{}`", what, value));
+}
+
+fn color(value: &str) -> Result<Color, String> {
+    if !is_hex_color(value) {
+        return Err(format!("invalid color `{}`, use #rrggbb", value));
+    }
+    return Ok(hex_color(value, 1.0));
+}
+
+fn block_type(value: &str) -> Result<BlockType, String> {
+    return BlockType::from_name(value).ok_or(format!("unknown block type `{}`", value));
+}
+
+// the arguments left after taking out the given flags, and which flags were there
+fn flags<'a>(args: &[&'a str], names: &[&str]) -> (Vec<&'a str>, Vec<bool>) {
+    let rest = args
+        .iter()
+        .copied()
+        .filter(|arg| !names.contains(arg))
+        .collect();
+    let found = names.iter().map(|name| args.contains(name)).collect();
+    return (rest, found);
+}
+
+fn help(_: &mut Game, args: &[&str]) -> Result<String, String> {
+    if let Some(name) = args.first() {
+        let command = COMMANDS
+            .iter()
+            .find(|command| command.name == *name)
+            .ok_or(format!("unknown command `{}`", name))?;
+        return Ok(format!("{}\n  {}", command.usage, command.help));
+    }
+    let lines: Vec<String> = COMMANDS
+        .iter()
+        .map(|command| format!("{:<48} {}", command.usage, command.help))
+        .collect();
+    return Ok(lines.join("\n"));
+}
+
+fn teleport(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let pos = SubGridPos {
+        x: number(args, 0, "x")?,
+        y: number(args, 1, "y")?,
+    };
+    game.world.player.teleport(pos);
+    game.world.generate_nearby_chunks();
+    game.world.camera.snap_to(pos);
+    return Ok(format!("teleported to {}, {}", pos.x, pos.y));
+}
+
+fn give(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let block_type = block_type(args.first().ok_or("missing type")?)?;
+    game.world.placer.set_block_type(block_type);
+    if let Some(value) = args.get(1) {
+        game.world.placer.set_color(color(value)?);
+    }
+    return Ok(format!("placing {}", block_type.name()));
+}
+
+// `air` means removing whatever is there
+fn block_or_air(game: &Game, kind: &str, color_arg: Option<&str>) -> Result<Option<Block>, String> {
+    if kind == "air" {
+        return Ok(None);
+    }
+    let block_type = block_type(kind)?;
+    let color = match color_arg {
+        Some(value) => color(value)?,
+        None => game.world.placer.calc_hsl_color(),
+    };
+    return Ok(Some(Block::new(block_type, color, BlockOverlay::None)));
+}
+
+fn set_block(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let (args, found) = flags(args, &["bg"]);
+    let pos = GridPos::new(number(&args, 0, "x")?, number(&args, 1, "y")?, found[0]);
+    let kind = args.get(2).ok_or("missing type")?;
+    if !inside_world(pos) {
+        return Err(format!("y {} is outside the world", pos.y));
+    }
+    match block_or_air(game, kind, args.get(3).copied())? {
+        Some(block) => {
+            game.world.place_blocks(vec![(pos, block)]);
+        }
+        None => {
+            game.world.remove_block(pos);
+        }
+    }
+    return Ok(format!("set {}, {} to {}", pos.x, pos.y, kind));
+}
+
+fn fill(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let (args, found) = flags(args, &["bg"]);
+    let corner = |x: usize, y: usize| -> Result<GridPos, String> {
+        return Ok(GridPos::new(
+            number(&args, x, "x")?,
+            number(&args, y, "y")?,
+            false,
+        ));
+    };
+    let area = GridRect::new(corner(0, 1)?, corner(2, 3)?);
+    let cells = area.width() as i64 * area.height() as i64;
+    if cells > MAX_FILL {
+        return Err(format!("{} cells is too many, at most {}", cells, MAX_FILL));
+    }
+    let value = args.get(4).ok_or("missing color")?;
+
+    let bg = found[0];
+    let cells = area.cells().filter(|pos| pos.bg == bg);
+    if *value == "air" {
+        let removed = cells.filter(|pos| game.world.remove_block(*pos).is_some());
+        return Ok(format!("removed {} blocks", removed.count()));
+    }
+    let kind = args.get(5).copied().unwrap_or("solid");
+    let block = Block::new(block_type(kind)?, color(value)?, BlockOverlay::None);
+    let blocks: Vec<(GridPos, Block)> = cells.map(|pos| (pos, block)).collect();
+    let count = game.world.place_blocks(blocks);
+    return Ok(format!("placed {} blocks", count));
+}
+
+// hh:mm, a fraction of the day or one of a few names
+fn parse_time(value: &str) -> Result<f32, String> {
+    let named = match value {
+        "midnight" => Some(0.0),
+        "day" => Some(0.3),
+        "noon" => Some(0.5),
+        "night" => Some(0.8),
+        _ => None,
+    };
+    if let Some(time) = named {
+        return Ok(time);
+    }
+    if let Some((hours, minutes)) = value.split_once(':') {
+        let hours: u32 = hours
+            .parse()
+            .map_err(|_| format!("invalid time `{}`", value))?;
+        let minutes: u32 = minutes
+            .parse()
+            .map_err(|_| format!("invalid time `{}`", value))?;
+        if hours >= 24 || minutes >= 60 {
+            return Err(format!("invalid time `{}`", value));
+        }
+        return Ok((hours as f32 + minutes as f32 / 60.0) / 24.0);
+    }
+    let time: f32 = value
+        .parse()
+        .map_err(|_| format!("invalid time `{}`", value))?;
+    if !(0.0..=1.0).contains(&time) {
+        return Err("a fraction of the day goes from 0 to 1".to_owned());
+    }
+    return Ok(time);
+}
+
+fn time(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    match args {
+        [] => {}
+        ["set", value] => game.world.clock.time = parse_time(value)?,
+        _ => return Err("expected `time` or `time set <time>`".to_owned()),
+    }
+    let (hours, minutes) = game.world.clock.clock_time();
+    return Ok(format!("it's {:02}:{:02}", hours, minutes));
+}
+
+fn game_mode(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    if let Some(name) = args.first() {
+        game.world.player.mode =
+            GameMode::from_name(name).ok_or(format!("unknown game mode `{}`", name))?;
+    }
+    return Ok(format!("game mode: {}", game.world.player.mode.name()));
+}
+
+fn weather(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let Some(name) = args.first() else {
+        let weather = &game.world.weather;
+        return Ok(format!("weather: {}", weather.kind.name()));
+    };
+    let kind = WeatherKind::from_name(name).ok_or(format!("unknown weather `{}`", name))?;
+    game.world.weather.set(kind);
+    return Ok(format!("weather turning to {}", kind.name()));
+}
+
+fn seed(game: &mut Game, _: &[&str]) -> Result<String, String> {
+    return Ok(format!("seed: {}", game.world.terrain.seed));
+}
+
+fn save(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let path = args.first().copied().unwrap_or(QUICKSAVE_PATH);
+    save_world(&game.world, path)?;
+    return Ok(format!("saved world to {}", path));
+}
+
+fn load(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let path = args.first().copied().unwrap_or(QUICKSAVE_PATH);
+    game.replace_world(load_world(path)?);
+    return Ok(format!("loaded {}", path));
+}
+
+fn import(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let (args, found) = flags(args, &["bg", "snap"]);
+    let path = args.first().ok_or("missing png")?;
+    let origin = GridPos::new(number(&args, 1, "x")?, number(&args, 2, "y")?, found[0]);
+    let blocks = image_blocks(&load_png(path)?, origin, found[1]);
+    let count = game.world.place_blocks(blocks);
+    return Ok(format!("imported {} blocks from {}", count, path));
+}
+
+fn export(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let path = args.first().ok_or("missing png")?;
+    let scale = if args.len() > 1 {
+        number(args, 1, "scale")?
+    } else {
+        EXPORT_SCALE
+    };
+    let area = if args.len() > 2 {
+        let corner = |x: usize, y: usize| -> Result<GridPos, String> {
+            return Ok(GridPos::new(
+                number(args, x, "x")?,
+                number(args, y, "y")?,
+                false,
+            ));
+        };
+        GridRect::new(corner(2, 3)?, corner(4, 5)?)
+    } else {
+        GridRect::around(&game.world.blocks).ok_or("there are no blocks to export")?
+    };
+    image_size(area, scale)?;
+    game.world.generate_columns(area.min_x, area.max_x);
+    let image = render_blocks(&game.world.blocks, area, scale, game.world.bg_color)?;
+    save_png(&image, path)?;
+    return Ok(format!(
+        "exported {}x{} pixels to {}",
+        image.width, image.height, path
+    ));
+}
+
+fn map(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let path = args.get(1).ok_or("missing path")?;
+    match args.first().copied() {
+        Some("import") => {
+            let fallback = if args.len() > 2 {
+                (number(args, 2, "x")?, number(args, 3, "y")?)
+            } else {
+                let pos = game.world.player.pos;
+                (pos.x as i32, pos.y as i32)
+            };
+            import_map(&mut game.world, path, fallback)?;
+            return Ok(format!("imported map {}", path));
+        }
+        Some("export") => {
+            export_map(&game.world, path)?;
+            return Ok(format!("exported map to {}", path));
+        }
+        _ => return Err("expected import or export".to_owned()),
+    }
+}
+
+fn profile(_: &mut Game, args: &[&str]) -> Result<String, String> {
+    match args.first().copied() {
+        Some("start") => {
+            if is_recording() {
+                return Err("already recording".to_owned());
+            }
+            start_recording();
+            return Ok("recording frame timings".to_owned());
+        }
+        Some("stop") => {
+            let path = args.get(1).copied().unwrap_or(PROFILE_PATH);
+            let frames = save_recording(path)?;
+            return Ok(format!("wrote {} frames to {}", frames, path));
+        }
+        _ => return Err("expected start or stop".to_owned()),
+    }
+}
+
+fn no_words() -> Vec<&'static str> {
+    return Vec::new();
+}
+
+fn command_names() -> Vec<&'static str> {
+    return COMMANDS.iter().map(|command| command.name).collect();
+}
+
+fn block_names() -> Vec<&'static str> {
+    return BlockType::ALL.iter().map(|kind| kind.name()).collect();
+}
+
+fn block_words() -> Vec<&'static str> {
+    let mut words = block_names();
+    words.extend(["air", "bg"]);
+    return words;
+}
+
+fn time_words() -> Vec<&'static str> {
+    return vec!["set", "day", "noon", "night", "midnight"];
+}
+
+fn mode_names() -> Vec<&'static str> {
+    return GameMode::ALL.iter().map(|mode| mode.name()).collect();
+}
+
+fn weather_names() -> Vec<&'static str> {
+    return WeatherKind::ALL.iter().map(|kind| kind.name()).collect();
+}
+
+fn import_words() -> Vec<&'static str> {
+    return vec!["bg", "snap"];
+}
+
+fn map_words() -> Vec<&'static str> {
+    return vec!["import", "export"];
+}
+
+fn profile_words() -> Vec<&'static str> {
+    return vec!["start", "stop"];
+}
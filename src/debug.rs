@@ -1,7 +1,205 @@
 use crate::game::Game;
+use crate::light::SKY_LIMIT;
+use crate::profiler::render_graph;
+use crate::settings::key;
+use crate::settings::key_name;
+use crate::settings::Action;
+use crate::terrain::CHUNK_WIDTH;
+use crate::terrain::WORLD_BOTTOM;
 use crate::utils::draw_text_ex_shadow;
+use crate::vis::VisRange;
+use crate::world::World;
+use crate::BLOCK_SIZE;
 use crate::BUILD_VERSION;
 use macroquad::prelude::*;
+use std::ops::RangeInclusive;
+
+// how much of the view the culling overlay cuts off each side, so what gets
+// culled happens on screen
+const CULL_INSET: f32 = 0.25;
+
+// overlays for chasing down physics and rendering bugs, F1-F4 toggle them,
+// F6 freezes the world so `.` can step it one update at a time and P shows
+// the frame profiler
+#[derive(Default)]
+pub struct DebugView {
+    // player hitbox, the cells it was tested against and what blocked it
+    pub collision: bool,
+    pub culling: bool,
+    pub chunks: bool,
+    pub entities: bool,
+    pub stepping: bool,
+    pub profiler: bool,
+}
+
+impl DebugView {
+    pub fn input(&mut self) {
+        if is_key_pressed(key(Action::DebugCollision)) {
+            self.collision = !self.collision;
+        }
+        if is_key_pressed(key(Action::DebugCulling)) {
+            self.culling = !self.culling;
+        }
+        if is_key_pressed(key(Action::DebugChunks)) {
+            self.chunks = !self.chunks;
+        }
+        if is_key_pressed(key(Action::DebugEntities)) {
+            self.entities = !self.entities;
+        }
+        if is_key_pressed(key(Action::FrameStepping)) {
+            self.stepping = !self.stepping;
+        }
+        if is_key_pressed(key(Action::Profiler)) {
+            self.profiler = !self.profiler;
+        }
+    }
+
+    // false while stepping, apart from the frame the step key (.) is pressed
+    pub fn should_update(&self) -> bool {
+        return !self.stepping || is_key_pressed(key(Action::StepFrame));
+    }
+
+    // what counts as on screen, shrunk while the culling overlay is on
+    pub fn cull_range(&self, view: VisRange) -> VisRange {
+        if !self.culling {
+            return view;
+        }
+        let inset_x = (view.max_x - view.min_x) * CULL_INSET;
+        let inset_y = (view.max_y - view.min_y) * CULL_INSET;
+        return VisRange::new(
+            view.min_x + inset_x,
+            view.min_y + inset_y,
+            view.max_x - inset_x,
+            view.max_y - inset_y,
+        );
+    }
+
+    fn summary(&self) -> String {
+        let name = |action: Action| key_name(key(action));
+        let flags = [
+            (
+                format!("{} collision", name(Action::DebugCollision)),
+                self.collision,
+            ),
+            (
+                format!("{} culling", name(Action::DebugCulling)),
+                self.culling,
+            ),
+            (format!("{} chunks", name(Action::DebugChunks)), self.chunks),
+            (
+                format!("{} entities", name(Action::DebugEntities)),
+                self.entities,
+            ),
+            (
+                format!(
+                    "{} stepping ({} to step)",
+                    name(Action::FrameStepping),
+                    name(Action::StepFrame)
+                ),
+                self.stepping,
+            ),
+            (
+                format!("{} profiler", name(Action::Profiler)),
+                self.profiler,
+            ),
+        ];
+        let on: Vec<&str> = flags
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        return on.join(", ");
+    }
+}
+
+// everything that's drawn in world space, with the world camera still set.
+// `vis` is the culling range and `chunks` the chunks that were drawn
+pub fn draw_world_overlays(world: &World, vis: &VisRange, chunks: RangeInclusive<i32>) {
+    let debug = &world.debug;
+    // one screen pixel, a world pixel is half the zoom wide
+    let line = 2.0 / world.camera.zoom;
+
+    if debug.chunks {
+        let top = SKY_LIMIT as f32 * BLOCK_SIZE;
+        let bottom = WORLD_BOTTOM as f32 * BLOCK_SIZE;
+        for chunk_x in *chunks.start()..=*chunks.end() + 1 {
+            let x = (chunk_x * CHUNK_WIDTH) as f32 * BLOCK_SIZE;
+            draw_line(x, top, x, bottom, line, Color::new(1.0, 1.0, 0.0, 0.6));
+        }
+    }
+
+    if debug.culling {
+        let first_x = (*chunks.start() * CHUNK_WIDTH) as f32 * BLOCK_SIZE;
+        let last_x = ((*chunks.end() + 1) * CHUNK_WIDTH) as f32 * BLOCK_SIZE;
+        draw_rectangle_lines(
+            first_x,
+            vis.min_y,
+            last_x - first_x,
+            vis.max_y - vis.min_y,
+            line * 2.0,
+            Color::new(0.0, 0.6, 1.0, 0.6),
+        );
+        draw_rectangle_lines(
+            vis.min_x,
+            vis.min_y,
+            vis.max_x - vis.min_x,
+            vis.max_y - vis.min_y,
+            line * 2.0,
+            MAGENTA,
+        );
+    }
+
+    if debug.entities {
+        for entity in &world.entities {
+            let hitbox = entity.hitbox();
+            draw_rectangle_lines(
+                hitbox.x * BLOCK_SIZE,
+                hitbox.y * BLOCK_SIZE,
+                hitbox.w * BLOCK_SIZE,
+                hitbox.h * BLOCK_SIZE,
+                line,
+                ORANGE,
+            );
+        }
+    }
+
+    if debug.collision {
+        let collision = &world.player.collision;
+        for (pos, solid) in &collision.tested {
+            let color = if *solid {
+                Color::new(1.0, 0.5, 0.0, 0.35)
+            } else {
+                Color::new(1.0, 1.0, 1.0, 0.1)
+            };
+            draw_cell(pos.x, pos.y, color);
+        }
+        if let Some(pos) = collision.x_blocker {
+            draw_cell(pos.x, pos.y, Color::new(1.0, 0.0, 0.0, 0.6));
+        }
+        if let Some(pos) = collision.y_blocker {
+            draw_cell(pos.x, pos.y, Color::new(0.0, 0.0, 1.0, 0.6));
+        }
+        let pos = world.player.get_pos();
+        draw_rectangle_lines(
+            pos.x * BLOCK_SIZE,
+            pos.y * BLOCK_SIZE,
+            3.0 * BLOCK_SIZE,
+            3.0 * BLOCK_SIZE,
+            line,
+            GREEN,
+        );
+    }
+}
+
+fn draw_cell(x: i32, y: i32, color: Color) {
+    draw_rectangle(
+        x as f32 * BLOCK_SIZE,
+        y as f32 * BLOCK_SIZE,
+        BLOCK_SIZE,
+        BLOCK_SIZE,
+        color,
+    );
+}
 
 pub fn draw_debug_text(game: &Game, font: macroquad::text::Font) {
     let mut messages: Vec<String> = Vec::new();
@@ -21,9 +219,36 @@ pub fn draw_debug_text(game: &Game, font: macroquad::text::Font) {
         player_vel.0, player_vel.1
     ));
 
+    let (hours, minutes) = game.world.clock.clock_time();
+    messages.push(format!("time: {:02}:{:02}", hours, minutes));
+
+    let weather = &game.world.weather;
+    messages.push(format!(
+        "weather: {} ({:.2}), wind: {:.3}, particles: {}",
+        weather.kind.name(),
+        weather.intensity,
+        weather.wind,
+        game.world.particles.count()
+    ));
+
+    messages.push(format!("player health: {:.0}", game.world.player.health));
+
+    messages.push(format!(
+        "placer type: {}, active cells: {}",
+        game.world.placer.get_block_type().name(),
+        game.world.physics.active_count()
+    ));
+
+    messages.push(format!("block meshes: {}", game.world.meshes.mesh_count()));
+
+    let overlays = game.world.debug.summary();
+    if !overlays.is_empty() {
+        messages.push(format!("debug: {}", overlays));
+    }
+
     for (index, message) in messages.iter().enumerate() {
         draw_text_ex_shadow(
-            &message,
+            message,
             10.0,
             20.0 * (index + 1) as f32,
             macroquad::text::TextParams {
@@ -34,4 +259,8 @@ pub fn draw_debug_text(game: &Game, font: macroquad::text::Font) {
             },
         );
     }
+
+    if game.world.debug.profiler {
+        render_graph(&font, 10.0, 20.0 * messages.len() as f32 + 10.0);
+    }
 }
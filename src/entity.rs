@@ -1,8 +1,20 @@
+use crate::particle::ParticleSystem;
 use crate::position::SubGridPos;
+use macroquad::prelude::Rect;
 
 pub trait Entity {
-    fn spawn(&mut self);
-    fn update(&mut self);
+    // `id` is what the world knows it by from now on, no two entities share one
+    fn spawn(&mut self, id: u64);
+    fn update(&mut self, particles: &mut ParticleSystem);
     fn render(&self);
+    fn get_id(&self) -> u64;
     fn get_pos(&self) -> SubGridPos;
+    // in blocks per update
+    fn get_vel(&self) -> (f32, f32);
+    // the area it takes up, in blocks
+    fn hitbox(&self) -> Rect;
+    // what sort of entity it is, like `prop`
+    fn kind(&self) -> &str;
+    // anything else worth showing in the inspector, as names and values
+    fn inspect(&self) -> Vec<(String, String)>;
 }
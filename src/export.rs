@@ -0,0 +1,255 @@
+use crate::atlas::atlas_image;
+use crate::block::Block;
+use crate::mesh::block_rect;
+use crate::mesh::drawn_level;
+use crate::mesh::BG_SHADE;
+use crate::position::GridPos;
+use crate::vis::VisRange;
+use crate::BLOCK_SIZE;
+use macroquad::miniquad::date;
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+// a rectangle of cells, both corners included
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GridRect {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+}
+
+impl GridRect {
+    // the corners can be given in any order, the layer is ignored
+    pub fn new(a: GridPos, b: GridPos) -> Self {
+        GridRect {
+            min_x: a.x.min(b.x),
+            min_y: a.y.min(b.y),
+            max_x: a.x.max(b.x),
+            max_y: a.y.max(b.y),
+        }
+    }
+
+    // the smallest rect holding every block, None when there are no blocks
+    pub fn around(blocks: &HashMap<GridPos, Block>) -> Option<Self> {
+        let mut positions = blocks.keys();
+        let first = *positions.next()?;
+        let mut rect = GridRect::new(first, first);
+        for pos in positions {
+            rect.min_x = rect.min_x.min(pos.x);
+            rect.min_y = rect.min_y.min(pos.y);
+            rect.max_x = rect.max_x.max(pos.x);
+            rect.max_y = rect.max_y.max(pos.y);
+        }
+        return Some(rect);
+    }
+
+    // every cell touched by a range of world pixels
+    pub fn from_vis(view: &VisRange) -> Self {
+        return GridRect {
+            min_x: (view.min_x / BLOCK_SIZE).floor() as i32,
+            min_y: (view.min_y / BLOCK_SIZE).floor() as i32,
+            max_x: (view.max_x / BLOCK_SIZE).floor() as i32,
+            max_y: (view.max_y / BLOCK_SIZE).floor() as i32,
+        };
+    }
+
+    // every cell in the background layer and then every cell in the foreground
+    pub fn cells(&self) -> impl Iterator<Item = GridPos> {
+        let rect = *self;
+        return [true, false].into_iter().flat_map(move |bg| {
+            (rect.min_y..=rect.max_y)
+                .flat_map(move |y| (rect.min_x..=rect.max_x).map(move |x| GridPos::new(x, y, bg)))
+        });
+    }
+
+    pub fn width(&self) -> i32 {
+        return self.max_x - self.min_x + 1;
+    }
+
+    pub fn height(&self) -> i32 {
+        return self.max_y - self.min_y + 1;
+    }
+}
+
+// `prefix-<unix time>.png` in the working directory, so nothing gets overwritten
+pub fn timestamped_path(prefix: &str) -> String {
+    return format!("{}-{}.png", prefix, date::now() as u64);
+}
+
+// writes whatever has been drawn to the screen so far this frame
+pub fn save_screenshot(path: &str) -> Result<(), String> {
+    let screen = get_screen_data();
+    // the framebuffer comes back bottom row first
+    let row = screen.width as usize * 4;
+    let mut bytes: Vec<u8> = Vec::with_capacity(screen.bytes.len());
+    for line in screen.bytes.chunks_exact(row).rev() {
+        bytes.extend_from_slice(line);
+    }
+    let image = Image {
+        bytes,
+        width: screen.width,
+        height: screen.height,
+    };
+    return save_png(&image, path);
+}
+
+// unlike Image::export_png this doesn't flip the image or panic
+pub fn save_png(image: &Image, path: &str) -> Result<(), String> {
+    return image::save_buffer(
+        path,
+        &image.bytes,
+        image.width as u32,
+        image.height as u32,
+        image::ColorType::Rgba8,
+    )
+    .map_err(|err| format!("couldn't write {}: {}", path, err));
+}
+
+// how big an image of `area` would be, checked before anything is generated or
+// drawn for it
+pub fn image_size(area: GridRect, scale: u32) -> Result<(u16, u16), String> {
+    let width = area.width() as u64 * scale as u64;
+    let height = area.height() as u64 * scale as u64;
+    if scale == 0 || width > u16::MAX as u64 || height > u16::MAX as u64 {
+        return Err(format!(
+            "a {}x{} area at {} pixels per block doesn't fit in one image",
+            area.width(),
+            area.height(),
+            scale
+        ));
+    }
+    return Ok((width as u16, height as u16));
+}
+
+// draws `area` of the world into an image with `scale` pixels per block, the
+// same way the block meshes do but without any lighting. runs on the cpu so it
+// works for parts of the world that were never on screen, and without a window
+pub fn render_blocks(
+    blocks: &HashMap<GridPos, Block>,
+    area: GridRect,
+    scale: u32,
+    background: Color,
+) -> Result<Image, String> {
+    let (width, height) = image_size(area, scale)?;
+    let mut canvas = Canvas {
+        image: Image::gen_image_color(width, height, background),
+        area,
+        scale,
+    };
+    let mut overlays: HashMap<&'static str, Option<Image>> = HashMap::new();
+
+    // same order as the mesh passes: background, foreground, overlays, liquids
+    canvas.fill_blocks(blocks, false);
+    for pos in area.cells() {
+        let Some(block) = blocks.get(&pos) else {
+            continue;
+        };
+        let Some(name) = block.overlay.texture_name() else {
+            continue;
+        };
+        let Some(overlay) = overlays.entry(name).or_insert_with(|| atlas_image(name)) else {
+            continue;
+        };
+        let shade = if pos.bg { BG_SHADE } else { 1.0 };
+        let tint = Color::new(shade, shade, shade, block.color.a);
+        canvas.stamp(pos, overlay, tint);
+    }
+    canvas.fill_blocks(blocks, true);
+    return Ok(canvas.image);
+}
+
+// the image being rendered into and where it sits in the world
+struct Canvas {
+    image: Image,
+    area: GridRect,
+    scale: u32,
+}
+
+impl Canvas {
+    // either the liquid or the non-liquid blocks in the area
+    fn fill_blocks(&mut self, blocks: &HashMap<GridPos, Block>, liquids: bool) {
+        for pos in self.area.cells() {
+            let Some(block) = blocks.get(&pos) else {
+                continue;
+            };
+            if block.block_type.is_liquid() != liquids {
+                continue;
+            }
+            let shade = if pos.bg { BG_SHADE } else { 1.0 };
+            let mut color = block.color;
+            color.a *= block.block_type.opacity();
+            // emissive blocks are never shaded
+            if block.emission().is_none() {
+                color.r *= shade;
+                color.g *= shade;
+                color.b *= shade;
+            }
+            let rect = block_rect(block, pos, drawn_level(block, pos, blocks));
+            self.fill(rect, color);
+        }
+    }
+
+    // world pixels to image pixels
+    fn to_image(&self, x: f32, y: f32) -> (i64, i64) {
+        let per_pixel = self.scale as f32 / BLOCK_SIZE;
+        return (
+            ((x - self.area.min_x as f32 * BLOCK_SIZE) * per_pixel).round() as i64,
+            ((y - self.area.min_y as f32 * BLOCK_SIZE) * per_pixel).round() as i64,
+        );
+    }
+
+    // alpha blends over whatever is already there
+    fn blend(&mut self, x: i64, y: i64, color: Color) {
+        if x < 0 || y < 0 || x >= self.image.width as i64 || y >= self.image.height as i64 {
+            return;
+        }
+        let (x, y) = (x as u32, y as u32);
+        let under = self.image.get_pixel(x, y);
+        let a = color.a;
+        let out_a = a + under.a * (1.0 - a);
+        let mix = |top: f32, bottom: f32| {
+            if out_a <= 0.0 {
+                return 0.0;
+            }
+            return (top * a + bottom * under.a * (1.0 - a)) / out_a;
+        };
+        let out = Color::new(
+            mix(color.r, under.r),
+            mix(color.g, under.g),
+            mix(color.b, under.b),
+            out_a,
+        );
+        self.image.set_pixel(x, y, out);
+    }
+
+    // `rect` is in world pixels
+    fn fill(&mut self, rect: Rect, color: Color) {
+        let (left, top) = self.to_image(rect.x, rect.y);
+        let (right, bottom) = self.to_image(rect.right(), rect.bottom());
+        for y in top..bottom {
+            for x in left..right {
+                self.blend(x, y, color);
+            }
+        }
+    }
+
+    // stretches `texture` over the cell at `pos`, nearest neighbour like the game
+    fn stamp(&mut self, pos: GridPos, texture: &Image, tint: Color) {
+        let scale = self.scale as i64;
+        let left = (pos.x - self.area.min_x) as i64 * scale;
+        let top = (pos.y - self.area.min_y) as i64 * scale;
+        for y in 0..scale {
+            for x in 0..scale {
+                let texture_x = (x * texture.width as i64 / scale) as u32;
+                let texture_y = (y * texture.height as i64 / scale) as u32;
+                let mut color = texture.get_pixel(texture_x, texture_y);
+                color.r *= tint.r;
+                color.g *= tint.g;
+                color.b *= tint.b;
+                color.a *= tint.a;
+                self.blend(left + x, top + y, color);
+            }
+        }
+    }
+}
@@ -1,20 +1,190 @@
-use crate::world::{generate_test_world, World};
+use crate::console::run_command;
+use crate::console::Console;
+use crate::export::image_size;
+use crate::export::render_blocks;
+use crate::export::save_png;
+use crate::export::save_screenshot;
+use crate::export::timestamped_path;
+use crate::export::GridRect;
+use crate::import::image_blocks;
+use crate::import::load_png;
+use crate::position::GridPos;
+use crate::save::load_world;
+use crate::save::save_world;
+use crate::settings::key;
+use crate::settings::Action;
+use crate::tiled::export_map;
+use crate::tiled::import_map;
+use crate::ui::Ui;
+use crate::world::World;
+use crate::BLOCK_SIZE;
+use macroquad::prelude::*;
+use std::path::Path;
+
+// where F5/F9 save and load the world
+pub const QUICKSAVE_PATH: &str = "world.vxw";
+// what F10 places as blocks
+const IMPORT_PATH: &str = "import.png";
+// what F7 imports, the first one that exists, and where F8 exports to
+const MAP_IMPORT_PATHS: [&str; 2] = ["map.tmx", "map.tmj"];
+const MAP_EXPORT_PATH: &str = "export.tmj";
+// pixels per block in world exports
+pub const EXPORT_SCALE: u32 = 4;
 
 pub struct Game {
     pub world: World,
+    pub console: Console,
 }
 
 impl Game {
-    pub fn new() -> Self {
+    pub fn new(world: World) -> Self {
         Game {
-            world: generate_test_world(),
+            world,
+            console: Console::new(),
         }
     }
 
     pub fn update(&mut self) {
-        self.world.update();
+        if let Some(line) = self.console.update() {
+            match run_command(self, &line) {
+                Ok(output) => self.console.print(&output, false),
+                Err(err) => self.console.print(&err, true),
+            }
+        }
+        // the world waits while the console has the keyboard
+        if self.console.open {
+            return;
+        }
+        self.world.debug.input();
+
+        if is_key_pressed(key(Action::QuickSave)) {
+            match save_world(&self.world, QUICKSAVE_PATH) {
+                Ok(()) => self
+                    .console
+                    .print(&format!("saved world to {}", QUICKSAVE_PATH), false),
+                Err(err) => self.console.print(&err, true),
+            }
+        }
+        if is_key_pressed(key(Action::QuickLoad)) {
+            match load_world(QUICKSAVE_PATH) {
+                Ok(world) => {
+                    self.replace_world(world);
+                    self.console
+                        .print(&format!("loaded {}", QUICKSAVE_PATH), false);
+                }
+                Err(err) => self.console.print(&err, true),
+            }
+        }
+        // F11 exports every block there is, and just what's on screen with the
+        // inspect key (shift) held
+        if is_key_pressed(key(Action::ExportWorld)) {
+            let area = if is_key_down(key(Action::Inspect)) {
+                Some(GridRect::from_vis(&self.world.camera.visible()))
+            } else {
+                GridRect::around(&self.world.blocks)
+            };
+            match area {
+                Some(area) => self.export_world(area),
+                None => self.console.print("there are no blocks to export", true),
+            }
+        }
+        // maps without an origin of their own go under the mouse
+        if is_key_pressed(key(Action::ImportMap)) {
+            let hovered = self.hovered_cell(false);
+            match MAP_IMPORT_PATHS
+                .iter()
+                .find(|path| Path::new(path).exists())
+            {
+                Some(path) => match import_map(&mut self.world, path, (hovered.x, hovered.y)) {
+                    Ok(()) => self.console.print(&format!("imported map {}", path), false),
+                    Err(err) => self.console.print(&err, true),
+                },
+                None => self.console.print(
+                    &format!("no {} to import", MAP_IMPORT_PATHS.join(" or ")),
+                    true,
+                ),
+            }
+        }
+        if is_key_pressed(key(Action::ExportMap)) {
+            match export_map(&self.world, MAP_EXPORT_PATH) {
+                Ok(()) => self
+                    .console
+                    .print(&format!("exported map to {}", MAP_EXPORT_PATH), false),
+                Err(err) => self.console.print(&err, true),
+            }
+        }
+        // F10 puts the import image down with its corner under the mouse,
+        // shift (inspect) puts it in the background and ctrl (color wheel) snaps it
+        // to the placer's colors
+        if is_key_pressed(key(Action::ImportImage)) {
+            let origin = self.hovered_cell(is_key_down(key(Action::Inspect)));
+            match load_png(IMPORT_PATH) {
+                Ok(image) => {
+                    let quantize = is_key_down(key(Action::ColorWheel));
+                    let blocks = image_blocks(&image, origin, quantize);
+                    let count = self.world.place_blocks(blocks);
+                    self.console.print(
+                        &format!("imported {} blocks from {}", count, IMPORT_PATH),
+                        false,
+                    );
+                }
+                Err(err) => self.console.print(&err, true),
+            }
+        }
+        if self.world.debug.should_update() {
+            self.world.update();
+        }
     }
-    pub fn render(&mut self) {
-        self.world.render();
+
+    // swaps in another world, keeping the debug overlays and how the camera
+    // was set up
+    pub fn replace_world(&mut self, mut world: World) {
+        world.debug = std::mem::take(&mut self.world.debug);
+        world.camera.settings = self.world.camera.settings;
+        world.camera.zoom = self.world.camera.zoom;
+        self.world = world;
+    }
+
+    // `input` is false while a menu is open over the game
+    pub fn render(&mut self, input: bool, ui: &mut Ui) {
+        self.world.render(input && !self.console.open);
+        if !self.world.minimap.open {
+            self.world.placer.render_hud(ui);
+        }
+        // taken before the debug text goes on top
+        if input && !self.console.open && is_key_pressed(key(Action::Screenshot)) {
+            let path = timestamped_path("screenshot");
+            match save_screenshot(&path) {
+                Ok(()) => self
+                    .console
+                    .print(&format!("saved screenshot to {}", path), false),
+                Err(err) => self.console.print(&err, true),
+            }
+        }
+    }
+
+    fn hovered_cell(&self, bg: bool) -> GridPos {
+        let mouse = mouse_position();
+        let world_pos = self
+            .world
+            .camera
+            .camera()
+            .screen_to_world(vec2(mouse.0, mouse.1))
+            / BLOCK_SIZE;
+        return GridPos::new(world_pos.x.floor() as i32, world_pos.y.floor() as i32, bg);
+    }
+
+    fn export_world(&mut self, area: GridRect) {
+        let path = timestamped_path("world");
+        if image_size(area, EXPORT_SCALE).is_ok() {
+            self.world.generate_columns(area.min_x, area.max_x);
+        }
+        let image = render_blocks(&self.world.blocks, area, EXPORT_SCALE, self.world.bg_color);
+        match image.and_then(|image| save_png(&image, &path)) {
+            Ok(()) => self
+                .console
+                .print(&format!("exported world to {}", path), false),
+            Err(err) => self.console.print(&err, true),
+        }
     }
 }
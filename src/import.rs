@@ -0,0 +1,34 @@
+use crate::block::Block;
+use crate::block::BlockOverlay;
+use crate::block::BlockType;
+use crate::placer::snap_to_palette;
+use crate::position::GridPos;
+use macroquad::prelude::*;
+use std::fs;
+
+pub fn load_png(path: &str) -> Result<Image, String> {
+    let bytes = fs::read(path).map_err(|err| format!("couldn't read {}: {}", path, err))?;
+    return Image::from_file_with_format(&bytes, Some(ImageFormat::Png))
+        .map_err(|err| format!("couldn't decode {}: {}", path, err));
+}
+
+// one solid block per pixel with the pixel's color, fully transparent pixels are
+// left empty. the top left pixel lands on `origin`, which also picks the layer.
+// `quantize` snaps every color to one the placer could have picked
+pub fn image_blocks(image: &Image, origin: GridPos, quantize: bool) -> Vec<(GridPos, Block)> {
+    let mut blocks: Vec<(GridPos, Block)> = Vec::new();
+    for y in 0..image.height as u32 {
+        for x in 0..image.width as u32 {
+            let mut color = image.get_pixel(x, y);
+            if color.a <= 0.0 {
+                continue;
+            }
+            if quantize {
+                color = snap_to_palette(color);
+            }
+            let pos = GridPos::new(origin.x + x as i32, origin.y + y as i32, origin.bg);
+            blocks.push((pos, Block::new(BlockType::Solid, color, BlockOverlay::None)));
+        }
+    }
+    return blocks;
+}
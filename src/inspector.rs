@@ -0,0 +1,157 @@
+use crate::block::Block;
+use crate::light::MAX_LIGHT;
+use crate::position::GridPos;
+use crate::settings::key;
+use crate::settings::Action;
+use crate::utils::color_to_hex;
+use crate::utils::draw_text_ex_shadow;
+use crate::world::World;
+use crate::BLOCK_SIZE;
+use hsl::HSL;
+use macroquad::prelude::*;
+
+const FONT_SIZE: u16 = 14;
+const LINE_HEIGHT: f32 = 16.0;
+const PADDING: f32 = 6.0;
+// how far the panel sits from the mouse
+const MOUSE_OFFSET: f32 = 16.0;
+
+// while the inspect key (shift) is held, a panel next to the mouse describing whatever is under
+// it. drawn with the default camera set, after everything else
+pub fn draw_inspector(world: &World, font: &Font) {
+    if !is_key_down(key(Action::Inspect)) || world.minimap.open {
+        return;
+    }
+    let mouse = Vec2::from(mouse_position());
+    let camera = world.camera.camera();
+    let hovered = camera.screen_to_world(mouse) / BLOCK_SIZE;
+
+    let mut lines: Vec<String> = Vec::new();
+    let player = world.player.get_pos();
+    if Rect::new(player.x, player.y, 3.0, 3.0).contains(hovered) {
+        let vel = world.player.get_vel();
+        lines.push("player".to_owned());
+        lines.push(format!("pos: {:.3}, {:.3}", player.x, player.y));
+        lines.push(format!("vel: {:.3}, {:.3}", vel.0, vel.1));
+        lines.push(format!("health: {:.0}", world.player.health));
+        lines.push(format!("mode: {}", world.player.mode.name()));
+        outline(camera, Rect::new(player.x, player.y, 3.0, 3.0));
+    }
+    for entity in &world.entities {
+        let hitbox = entity.hitbox();
+        if !hitbox.contains(hovered) {
+            continue;
+        }
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        let pos = entity.get_pos();
+        let vel = entity.get_vel();
+        lines.push(format!("{} #{}", entity.kind(), entity.get_id()));
+        lines.push(format!("pos: {:.3}, {:.3}", pos.x, pos.y));
+        lines.push(format!("vel: {:.3}, {:.3}", vel.0, vel.1));
+        for (name, value) in entity.inspect() {
+            lines.push(format!("{}: {}", name, value));
+        }
+        outline(camera, hitbox);
+    }
+
+    // the foreground block, or the background one when there's nothing in front
+    let x = hovered.x.floor() as i32;
+    let y = hovered.y.floor() as i32;
+    let cell = [false, true]
+        .into_iter()
+        .map(|bg| GridPos::new(x, y, bg))
+        .find_map(|pos| world.blocks.get(&pos).map(|block| (pos, block)));
+    if !lines.is_empty() {
+        lines.push(String::new());
+    }
+    match cell {
+        Some((pos, block)) => lines.extend(block_lines(world, pos, block)),
+        None => {
+            lines.push(format!("cell: {}, {}", x, y));
+            lines.push("empty".to_owned());
+            lines.push(light_line(world, x, y));
+        }
+    }
+
+    draw_panel(&lines, mouse, font);
+}
+
+fn block_lines(world: &World, pos: GridPos, block: &Block) -> Vec<String> {
+    let color = block.color;
+    let byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let hsl = HSL::from_rgb(&[byte(color.r), byte(color.g), byte(color.b)]);
+    let layer = if pos.bg { "background" } else { "foreground" };
+
+    let mut lines = vec![
+        format!("cell: {}, {} ({})", pos.x, pos.y, layer),
+        format!("kind: {}", block.block_type.name()),
+        format!(
+            "rgba: {}, {}, {}, {} ({})",
+            byte(color.r),
+            byte(color.g),
+            byte(color.b),
+            byte(color.a),
+            color_to_hex(color)
+        ),
+        format!("hsl: {:.1}, {:.3}, {:.3}", hsl.h, hsl.s, hsl.l),
+        format!("overlay: {}", block.overlay.name()),
+        format!("level: {:.3}", block.level),
+    ];
+    if let Some(emission) = block.emission() {
+        lines.push(format!("emits: {}", color_to_hex(emission)));
+    }
+    lines.push(light_line(world, pos.x, pos.y));
+    return lines;
+}
+
+fn light_line(world: &World, x: i32, y: i32) -> String {
+    let light = world.light.get(x, y);
+    return format!(
+        "light: sky {}, rgb {} {} {} (of {})",
+        light.sky, light.r, light.g, light.b, MAX_LIGHT
+    );
+}
+
+// `rect` is in blocks
+fn outline(camera: &Camera2D, rect: Rect) {
+    let top_left = camera.world_to_screen(rect.point() * BLOCK_SIZE);
+    let bottom_right = camera.world_to_screen((rect.point() + rect.size()) * BLOCK_SIZE);
+    let size = bottom_right - top_left;
+    draw_rectangle_lines(top_left.x, top_left.y, size.x, size.y, 2.0, YELLOW);
+}
+
+// next to the mouse, flipped to the other side when it would leave the screen
+fn draw_panel(lines: &[String], mouse: Vec2, font: &Font) {
+    let width = lines
+        .iter()
+        .map(|line| measure_text(line, Some(font), FONT_SIZE, 1.0).width)
+        .fold(0.0, f32::max)
+        + PADDING * 2.0;
+    let height = lines.len() as f32 * LINE_HEIGHT + PADDING * 2.0;
+    let mut x = mouse.x + MOUSE_OFFSET;
+    let mut y = mouse.y + MOUSE_OFFSET;
+    if x + width > screen_width() {
+        x = mouse.x - MOUSE_OFFSET - width;
+    }
+    if y + height > screen_height() {
+        y = (screen_height() - height).max(0.0);
+    }
+
+    draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.75));
+    draw_rectangle_lines(x, y, width, height, 1.0, GRAY);
+    for (index, line) in lines.iter().enumerate() {
+        draw_text_ex_shadow(
+            line,
+            x + PADDING,
+            y + PADDING + LINE_HEIGHT * (index + 1) as f32 - 4.0,
+            TextParams {
+                font: Some(font),
+                font_size: FONT_SIZE,
+                color: WHITE,
+                ..Default::default()
+            },
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use crate::block::Block;
+use crate::position::GridPos;
+use crate::terrain::WORLD_BOTTOM;
+use macroquad::prelude::*;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+pub const MAX_LIGHT: u8 = 15;
+// how far light travels, a change only has to be recomputed this far out
+const LIGHT_RADIUS: i32 = MAX_LIGHT as i32 + 1;
+// nothing is ever built above this, every column is open sky up here
+pub const SKY_LIMIT: i32 = -128;
+// darkest anything is drawn, so pitch black caves still show a hint of shape
+pub const MIN_BRIGHTNESS: f32 = 0.04;
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct CellLight {
+    pub sky: u8,
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl CellLight {
+    const FULL_SKY: CellLight = CellLight {
+        sky: MAX_LIGHT,
+        r: 0,
+        g: 0,
+        b: 0,
+    };
+
+    // each channel one step further away, entering a cell costs `cost`
+    fn spread(&self, cost: u8) -> CellLight {
+        CellLight {
+            sky: self.sky.saturating_sub(cost),
+            r: self.r.saturating_sub(cost),
+            g: self.g.saturating_sub(cost),
+            b: self.b.saturating_sub(cost),
+        }
+    }
+
+    fn max(&self, other: &CellLight) -> CellLight {
+        CellLight {
+            sky: self.sky.max(other.sky),
+            r: self.r.max(other.r),
+            g: self.g.max(other.g),
+            b: self.b.max(other.b),
+        }
+    }
+
+    // the light as the block shader takes it: sky, then red, green and blue
+    pub fn to_vec4(self) -> Vec4 {
+        let max = MAX_LIGHT as f32;
+        return vec4(
+            self.sky as f32 / max,
+            self.r as f32 / max,
+            self.g as f32 / max,
+            self.b as f32 / max,
+        );
+    }
+
+    // final color to multiply blocks with. `ambient` is how bright the sky is
+    pub fn to_color(self, ambient: Color) -> Color {
+        let sky = self.sky as f32 / MAX_LIGHT as f32;
+        let channel = |block: u8, ambient: f32| {
+            (block as f32 / MAX_LIGHT as f32)
+                .max(sky * ambient)
+                .max(MIN_BRIGHTNESS)
+        };
+        return Color::new(
+            channel(self.r, ambient.r),
+            channel(self.g, ambient.g),
+            channel(self.b, ambient.b),
+            1.0,
+        );
+    }
+}
+
+// light for a single column from `top` down to the bottom of the world,
+// anything above `top` is open sky
+struct LightColumn {
+    top: i32,
+    cells: Vec<CellLight>,
+}
+
+pub struct LightMap {
+    columns: HashMap<i32, LightColumn>,
+    // column ranges that need recomputing at the next update
+    dirty: Vec<(i32, i32)>,
+}
+
+impl LightMap {
+    pub fn new() -> Self {
+        LightMap {
+            columns: HashMap::new(),
+            dirty: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> CellLight {
+        let Some(column) = self.columns.get(&x) else {
+            return CellLight::FULL_SKY;
+        };
+        if y < column.top {
+            return CellLight::FULL_SKY;
+        }
+        return column
+            .cells
+            .get((y - column.top) as usize)
+            .copied()
+            .unwrap_or_default();
+    }
+
+    // a block changed somewhere in this column
+    pub fn mark_dirty(&mut self, x: i32) {
+        self.mark_dirty_range(x, x);
+    }
+
+    pub fn mark_dirty_range(&mut self, min_x: i32, max_x: i32) {
+        self.dirty
+            .push((min_x - LIGHT_RADIUS, max_x + LIGHT_RADIUS));
+    }
+
+    // recompute everything that was marked dirty since the last update, overlapping
+    // ranges are merged so a burst of changes is only handled once. returns the
+    // column ranges whose light may have changed
+    pub fn update(&mut self, blocks: &HashMap<GridPos, Block>) -> Vec<(i32, i32)> {
+        if self.dirty.is_empty() {
+            return Vec::new();
+        }
+        self.dirty.sort_by_key(|(min_x, _)| *min_x);
+        let mut merged: Vec<(i32, i32)> = Vec::new();
+        for (min_x, max_x) in self.dirty.drain(..) {
+            match merged.last_mut() {
+                Some(last) if min_x <= last.1 + 1 => last.1 = last.1.max(max_x),
+                _ => merged.push((min_x, max_x)),
+            }
+        }
+        for (min_x, max_x) in &merged {
+            self.recompute(*min_x, *max_x, blocks);
+        }
+        return merged;
+    }
+
+    fn recompute(&mut self, min_x: i32, max_x: i32, blocks: &HashMap<GridPos, Block>) {
+        // the columns just outside the range keep their light and feed into it
+        let grid_min_x = min_x - 1;
+        let width = (max_x - min_x + 3) as usize;
+
+        // first solid foreground block of every column, sky light stops there
+        let mut tops: Vec<i32> = Vec::with_capacity(width);
+        for i in 0..width {
+            let x = grid_min_x + i as i32;
+            let top = (SKY_LIMIT..WORLD_BOTTOM)
+                .find(|y| {
+                    blocks
+                        .get(&GridPos::new(x, *y, false))
+                        .is_some_and(|block| block.block_type.light_cost() > 1)
+                })
+                .unwrap_or(WORLD_BOTTOM);
+            tops.push(top);
+        }
+        let grid_top = *tops.iter().min().unwrap() - 1;
+        let height = (WORLD_BOTTOM - grid_top) as usize;
+        let index = |i: usize, y: i32| i * height + (y - grid_top) as usize;
+
+        let mut light: Vec<CellLight> = vec![CellLight::default(); width * height];
+        let mut cost: Vec<u8> = vec![1; width * height];
+        let mut queue: VecDeque<(usize, i32)> = VecDeque::new();
+
+        for (i, top) in tops.iter().enumerate() {
+            let x = grid_min_x + i as i32;
+            let border = i == 0 || i == width - 1;
+            for y in grid_top..WORLD_BOTTOM {
+                let cell = index(i, y);
+                if border {
+                    // columns that were never computed don't give off any light
+                    if self.columns.contains_key(&x) {
+                        light[cell] = self.get(x, y);
+                    }
+                    queue.push_back((i, y));
+                    continue;
+                }
+                if y < *top {
+                    light[cell] = CellLight::FULL_SKY;
+                    queue.push_back((i, y));
+                    continue;
+                }
+                if let Some(block) = blocks.get(&GridPos::new(x, y, false)) {
+                    cost[cell] = block.block_type.light_cost();
+                    if let Some(emission) = block.emission() {
+                        light[cell] = light[cell].max(&emission_light(emission));
+                        queue.push_back((i, y));
+                    }
+                }
+            }
+        }
+
+        // flood fill, a cell is revisited whenever one of its channels gets brighter
+        while let Some((i, y)) = queue.pop_front() {
+            let current = light[index(i, y)];
+            let neighbors = [
+                (i as i32 - 1, y),
+                (i as i32 + 1, y),
+                (i as i32, y - 1),
+                (i as i32, y + 1),
+            ];
+            for (ni, ny) in neighbors {
+                // border columns are fixed
+                if ni <= 0 || ni >= width as i32 - 1 || ny < grid_top || ny >= WORLD_BOTTOM {
+                    continue;
+                }
+                let cell = index(ni as usize, ny);
+                let spread = current.spread(cost[cell]);
+                let brighter = light[cell].max(&spread);
+                if brighter != light[cell] {
+                    light[cell] = brighter;
+                    queue.push_back((ni as usize, ny));
+                }
+            }
+        }
+
+        for i in 1..width - 1 {
+            let start = index(i, grid_top);
+            self.columns.insert(
+                grid_min_x + i as i32,
+                LightColumn {
+                    top: grid_top,
+                    cells: light[start..start + height].to_vec(),
+                },
+            );
+        }
+    }
+}
+
+fn emission_light(color: Color) -> CellLight {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * MAX_LIGHT as f32).round() as u8;
+    CellLight {
+        sky: 0,
+        r: channel(color.r),
+        g: channel(color.g),
+        b: channel(color.b),
+    }
+}
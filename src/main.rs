@@ -1,40 +1,116 @@
+// explicit returns are the house style
+#![allow(clippy::needless_return)]
+
 use macroquad::prelude::*;
 
+mod app;
+mod atlas;
+mod biome;
 mod block;
+mod camera;
+mod cli;
+mod clock;
+mod console;
 mod debug;
 mod entity;
+mod export;
 mod game;
+mod import;
+mod inspector;
+mod light;
+mod mesh;
+mod minimap;
+mod noise;
+mod parallax;
+mod particle;
+mod physics;
 mod placer;
 mod player;
 mod position;
+mod profiler;
+mod prop;
+mod save;
+mod settings;
+mod structure;
+mod terrain;
+mod tiled;
+mod ui;
 mod utils;
 mod vis;
+mod weather;
 mod world;
-use debug::draw_debug_text;
-use game::Game;
+use app::App;
+use cli::parse_args;
+use cli::run_tool;
+use cli::Command;
+use cli::LaunchOptions;
+use cli::USAGE;
+use macroquad::miniquad::conf::Platform;
+use settings::Settings;
+use settings::SETTINGS_PATH;
 
 const BUILD_VERSION: &str = "PROTO4";
 const BLOCK_SIZE: f32 = 6.0;
 
-fn macroquad_conf() -> Conf {
-    Conf {
-        window_title: format!("Vexalab {}", BUILD_VERSION),
-        high_dpi: true,
-        window_width: 900,
-        window_height: 600,
+// the window comes from the settings file, problems with it are reported once
+// the app loads it again. a size given on the command line wins
+fn macroquad_conf(options: &LaunchOptions) -> macroquad::conf::Conf {
+    let (settings, _) = Settings::load(SETTINGS_PATH);
+    let (width, height) = options
+        .window
+        .unwrap_or((settings.window_width, settings.window_height));
+    macroquad::conf::Conf {
+        miniquad_conf: Conf {
+            window_title: format!("Vexalab {}", BUILD_VERSION),
+            high_dpi: true,
+            window_width: width as i32,
+            window_height: height as i32,
+            fullscreen: settings.fullscreen,
+            platform: Platform {
+                swap_interval: Some(if settings.vsync { 1 } else { 0 }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        // room for two full chunk meshes per draw call
+        draw_call_vertex_capacity: 32768,
+        draw_call_index_capacity: 49152,
         ..Default::default()
     }
 }
 
-#[macroquad::main(macroquad_conf)]
-async fn main() {
+// the tools run and exit before a window is ever opened, so scripts can use
+// them on machines without a display
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(Command::Play(options)) => options,
+        Ok(command) => {
+            if let Err(err) = run_tool(command) {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
+    macroquad::Window::from_config(macroquad_conf(&options), run(options));
+}
+
+async fn run(options: LaunchOptions) {
     let font = load_ttf_font_from_bytes(include_bytes!("assets/consolas.ttf")).unwrap();
-    let mut game = Game::new();
+    let mut app = App::new(font.clone(), options);
     loop {
         clear_background(BLACK);
-        game.update();
-        game.render();
-        draw_debug_text(&game, font.clone());
-        next_frame().await
+        app.update();
+        if app.quit {
+            break;
+        }
+        app.render();
+        next_frame().await;
+        profiler::end_frame();
     }
 }
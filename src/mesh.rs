@@ -0,0 +1,319 @@
+use crate::atlas::atlas;
+use crate::block::Block;
+use crate::block::BlockOverlay;
+use crate::block::BlockType;
+use crate::light::LightMap;
+use crate::light::MIN_BRIGHTNESS;
+use crate::light::SKY_LIMIT;
+use crate::position::GridPos;
+use crate::profiler::count;
+use crate::terrain::chunk_of;
+use crate::terrain::CHUNK_WIDTH;
+use crate::terrain::WORLD_BOTTOM;
+use crate::BLOCK_SIZE;
+use macroquad::miniquad::BlendFactor;
+use macroquad::miniquad::BlendState;
+use macroquad::miniquad::BlendValue;
+use macroquad::miniquad::Equation;
+use macroquad::models::Vertex;
+use macroquad::prelude::*;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// quads per mesh, kept under what a single draw call can hold
+const MAX_MESH_QUADS: usize = 4096;
+// background blocks are drawn darker so they read as being further away
+pub const BG_SHADE: f32 = 0.6;
+
+// which pass a mesh belongs to, drawn in this order with the player before liquids
+#[derive(Clone, Copy)]
+pub enum MeshLayer {
+    Background,
+    Foreground,
+    Overlays,
+    Liquids,
+}
+
+// every block of one chunk baked into vertex meshes, one set per layer
+struct ChunkMesh {
+    // how many blocks went into it
+    blocks: usize,
+    background: Vec<Mesh>,
+    foreground: Vec<Mesh>,
+    overlays: Vec<Mesh>,
+    liquids: Vec<Mesh>,
+}
+
+impl ChunkMesh {
+    fn layer(&self, layer: MeshLayer) -> &Vec<Mesh> {
+        match layer {
+            MeshLayer::Background => &self.background,
+            MeshLayer::Foreground => &self.foreground,
+            MeshLayer::Overlays => &self.overlays,
+            MeshLayer::Liquids => &self.liquids,
+        }
+    }
+}
+
+// collects quads, starting a new mesh whenever the current one is full
+struct MeshBuilder {
+    meshes: Vec<Mesh>,
+    texture: Option<Texture2D>,
+}
+
+impl MeshBuilder {
+    fn new(texture: Option<Texture2D>) -> Self {
+        MeshBuilder {
+            meshes: Vec::new(),
+            texture,
+        }
+    }
+
+    // `light` is the cell's light as passed to the block shader
+    fn quad(&mut self, rect: Rect, uv: Rect, color: Color, light: Vec4) {
+        let full = self
+            .meshes
+            .last()
+            .is_none_or(|mesh| mesh.vertices.len() >= MAX_MESH_QUADS * 4);
+        if full {
+            self.meshes.push(Mesh {
+                vertices: Vec::new(),
+                indices: Vec::new(),
+                texture: self.texture.clone(),
+            });
+        }
+        let mesh = self.meshes.last_mut().unwrap();
+        let start = mesh.vertices.len() as u16;
+        let corners = [
+            (rect.x, rect.y, uv.x, uv.y),
+            (rect.right(), rect.y, uv.right(), uv.y),
+            (rect.right(), rect.bottom(), uv.right(), uv.bottom()),
+            (rect.x, rect.bottom(), uv.x, uv.bottom()),
+        ];
+        for (x, y, u, v) in corners {
+            mesh.vertices.push(Vertex {
+                position: vec3(x, y, 0.0),
+                uv: vec2(u, v),
+                color: color.into(),
+                normal: light,
+            });
+        }
+        mesh.indices
+            .extend([0, 1, 2, 0, 2, 3].map(|index| start + index));
+    }
+
+    fn finish(self) -> Vec<Mesh> {
+        return self.meshes;
+    }
+}
+
+// block meshes for every chunk that has been drawn, rebuilt only when something
+// in the chunk changes so the whole screen renders in a few draw calls
+pub struct BlockMeshes {
+    chunks: HashMap<i32, ChunkMesh>,
+    dirty: HashSet<i32>,
+    material: Material,
+}
+
+impl BlockMeshes {
+    pub fn new() -> Self {
+        BlockMeshes {
+            chunks: HashMap::new(),
+            dirty: HashSet::new(),
+            material: load_block_shader().unwrap(),
+        }
+    }
+
+    pub fn mesh_count(&self) -> usize {
+        return self
+            .chunks
+            .values()
+            .map(|chunk| {
+                chunk.background.len()
+                    + chunk.foreground.len()
+                    + chunk.overlays.len()
+                    + chunk.liquids.len()
+            })
+            .sum();
+    }
+
+    // blocks in the given chunks, as of when they were last built
+    pub fn block_count(&self, chunks: impl Iterator<Item = i32>) -> usize {
+        return chunks
+            .filter_map(|chunk_x| self.chunks.get(&chunk_x))
+            .map(|chunk| chunk.blocks)
+            .sum();
+    }
+
+    // a block or its light changed somewhere in these columns
+    pub fn mark_dirty_range(&mut self, min_x: i32, max_x: i32) {
+        for chunk_x in chunk_of(min_x)..=chunk_of(max_x) {
+            self.dirty.insert(chunk_x);
+        }
+    }
+
+    // rebuilds any of the given chunks that changed since they were last drawn
+    pub fn prepare(
+        &mut self,
+        chunks: impl Iterator<Item = i32>,
+        blocks: &HashMap<GridPos, Block>,
+        light: &LightMap,
+    ) {
+        for chunk_x in chunks {
+            if self.dirty.remove(&chunk_x) || !self.chunks.contains_key(&chunk_x) {
+                let mesh = self.build(chunk_x, blocks, light);
+                self.chunks.insert(chunk_x, mesh);
+            }
+        }
+    }
+
+    // draws one layer of the given chunks, they have to be prepared first.
+    // `ambient` is how bright the sky is
+    pub fn render(&self, chunks: impl Iterator<Item = i32>, layer: MeshLayer, ambient: Color) {
+        gl_use_material(&self.material);
+        self.material.set_uniform("ambient", ambient);
+        self.material.set_uniform("minBrightness", MIN_BRIGHTNESS);
+        for chunk_x in chunks {
+            let Some(chunk) = self.chunks.get(&chunk_x) else {
+                continue;
+            };
+            for mesh in chunk.layer(layer) {
+                draw_mesh(mesh);
+            }
+            count("draw calls", chunk.layer(layer).len() as u64);
+        }
+        gl_use_default_material();
+    }
+
+    fn build(&self, chunk_x: i32, blocks: &HashMap<GridPos, Block>, light: &LightMap) -> ChunkMesh {
+        let mut background = MeshBuilder::new(None);
+        let mut foreground = MeshBuilder::new(None);
+        // one builder per atlas page the overlays are on
+        let mut overlays: HashMap<usize, MeshBuilder> = HashMap::new();
+        let mut liquids = MeshBuilder::new(None);
+        let full_uv = Rect::new(0.0, 0.0, 1.0, 1.0);
+        let mut block_count = 0;
+
+        let start_x = chunk_x * CHUNK_WIDTH;
+        for x in start_x..start_x + CHUNK_WIDTH {
+            for y in SKY_LIMIT..WORLD_BOTTOM {
+                for bg in [true, false] {
+                    let pos = GridPos::new(x, y, bg);
+                    let Some(block) = blocks.get(&pos) else {
+                        continue;
+                    };
+                    block_count += 1;
+                    let cell_light = light.get(x, y).to_vec4();
+                    let shade = if bg { BG_SHADE } else { 1.0 };
+
+                    let mut color = block.color;
+                    color.a *= block.block_type.opacity();
+                    // emissive blocks are never darker than their own light
+                    let block_light = if block.emission().is_some() {
+                        vec4(1.0, 1.0, 1.0, 1.0)
+                    } else {
+                        color.r *= shade;
+                        color.g *= shade;
+                        color.b *= shade;
+                        cell_light
+                    };
+
+                    let target = if block.block_type.is_liquid() {
+                        &mut liquids
+                    } else if bg {
+                        &mut background
+                    } else {
+                        &mut foreground
+                    };
+                    let level = drawn_level(block, pos, blocks);
+                    target.quad(block_rect(block, pos, level), full_uv, color, block_light);
+
+                    if block.overlay != BlockOverlay::None {
+                        let tint = Color::new(shade, shade, shade, block.color.a);
+                        let cell = Rect::new(
+                            x as f32 * BLOCK_SIZE,
+                            y as f32 * BLOCK_SIZE,
+                            BLOCK_SIZE,
+                            BLOCK_SIZE,
+                        );
+                        let region = atlas().region(block.overlay.texture_name().unwrap());
+                        overlays
+                            .entry(region.page)
+                            .or_insert_with(|| {
+                                MeshBuilder::new(Some(atlas().page(region.page).clone()))
+                            })
+                            .quad(cell, region.uv, tint, cell_light);
+                    }
+                }
+            }
+        }
+
+        return ChunkMesh {
+            blocks: block_count,
+            background: background.finish(),
+            foreground: foreground.finish(),
+            overlays: overlays
+                .into_values()
+                .flat_map(|builder| builder.finish())
+                .collect(),
+            liquids: liquids.finish(),
+        };
+    }
+}
+
+// how full a block is drawn. liquid fills the whole cell when there's more of it
+// above so columns have no gaps
+pub fn drawn_level(block: &Block, pos: GridPos, blocks: &HashMap<GridPos, Block>) -> f32 {
+    if block.block_type.is_liquid() {
+        let above = GridPos::new(pos.x, pos.y - 1, pos.bg);
+        if blocks
+            .get(&above)
+            .is_some_and(|other| other.block_type == block.block_type)
+        {
+            return 1.0;
+        }
+    }
+    return block.level;
+}
+
+// where a block is drawn in world pixels, matching render_block
+pub fn block_rect(block: &Block, pos: GridPos, level: f32) -> Rect {
+    let x = pos.x as f32 * BLOCK_SIZE;
+    let y = pos.y as f32 * BLOCK_SIZE;
+    // partially filled cells are drawn from the bottom up
+    let height = BLOCK_SIZE * level;
+    // torches are a thin stick in the middle of the cell
+    if block.block_type == BlockType::Torch {
+        return Rect::new(
+            x + BLOCK_SIZE / 3.0,
+            y + BLOCK_SIZE - height,
+            BLOCK_SIZE / 3.0,
+            height,
+        );
+    }
+    return Rect::new(x, y + BLOCK_SIZE - height, BLOCK_SIZE, height);
+}
+
+fn load_block_shader() -> Result<Material, macroquad::Error> {
+    return load_material(
+        ShaderSource::Glsl {
+            vertex: include_str!("./assets/shaders/block.vert"),
+            fragment: include_str!("./assets/shaders/block.frag"),
+        },
+        MaterialParams {
+            uniforms: vec![
+                UniformDesc::new("ambient", UniformType::Float4),
+                UniformDesc::new("minBrightness", UniformType::Float1),
+            ],
+            pipeline_params: PipelineParams {
+                color_blend: Some(BlendState::new(
+                    Equation::Add,
+                    BlendFactor::Value(BlendValue::SourceAlpha),
+                    BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
+                )),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+}
@@ -0,0 +1,294 @@
+use crate::block::Block;
+use crate::light::SKY_LIMIT;
+use crate::mesh::BG_SHADE;
+use crate::position::GridPos;
+use crate::position::SubGridPos;
+use crate::settings::key;
+use crate::settings::Action;
+use crate::terrain::chunk_of;
+use crate::terrain::CHUNK_WIDTH;
+use crate::terrain::WORLD_BOTTOM;
+use macroquad::prelude::*;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// every chunk is one pixel per block, from the sky limit to the bottom of the world
+const MAP_HEIGHT: i32 = WORLD_BOTTOM - SKY_LIMIT;
+// corner minimap size and how many screen pixels one block takes up on it
+const MINIMAP_SIZE: f32 = 160.0;
+const MINIMAP_SCALE: f32 = 2.0;
+const MINIMAP_MARGIN: f32 = 10.0;
+// screen pixels per block on the full map
+const MIN_MAP_ZOOM: f32 = 0.5;
+const MAX_MAP_ZOOM: f32 = 12.0;
+const MAP_PAN_SPEED: f32 = 8.0;
+
+struct MapChunk {
+    image: Image,
+    texture: Texture2D,
+    // the image changed since it was last uploaded
+    dirty: bool,
+}
+
+// a picture of every explored chunk, kept in textures that are only touched
+// where blocks change so drawing either map costs a few draw calls
+pub struct Minimap {
+    chunks: HashMap<i32, MapChunk>,
+    dirty_cells: HashSet<(i32, i32)>,
+    // the full screen map is showing instead of the corner one
+    pub open: bool,
+    // what the full map is centered on, in blocks
+    center: Vec2,
+    zoom: f32,
+    // block under the mouse when a drag on the full map started
+    drag_anchor: Option<Vec2>,
+}
+
+impl Minimap {
+    pub fn new() -> Self {
+        Minimap {
+            chunks: HashMap::new(),
+            dirty_cells: HashSet::new(),
+            open: false,
+            center: vec2(0.0, 0.0),
+            zoom: 2.0,
+            drag_anchor: None,
+        }
+    }
+
+    // both layers of a cell share a pixel, so the layer doesn't matter
+    pub fn mark_dirty(&mut self, pos: GridPos) {
+        self.dirty_cells.insert((pos.x, pos.y));
+    }
+
+    // the map key (M) opens and closes the full map, which starts out on the player
+    pub fn update(
+        &mut self,
+        explored: &HashSet<i32>,
+        blocks: &HashMap<GridPos, Block>,
+        player_pos: SubGridPos,
+    ) {
+        if is_key_pressed(key(Action::Map)) {
+            self.open = !self.open;
+            self.center = vec2(player_pos.x, player_pos.y);
+            self.drag_anchor = None;
+        }
+        if self.open {
+            self.map_input();
+        }
+
+        for chunk_x in explored {
+            if !self.chunks.contains_key(chunk_x) {
+                self.chunks.insert(*chunk_x, build_chunk(*chunk_x, blocks));
+            }
+        }
+        for (x, y) in self.dirty_cells.drain() {
+            let Some(chunk) = self.chunks.get_mut(&chunk_of(x)) else {
+                continue;
+            };
+            if !(SKY_LIMIT..WORLD_BOTTOM).contains(&y) {
+                continue;
+            }
+            let local_x = (x - chunk_of(x) * CHUNK_WIDTH) as u32;
+            let color = cell_color(blocks, x, y);
+            chunk
+                .image
+                .set_pixel(local_x, (y - SKY_LIMIT) as u32, color);
+            chunk.dirty = true;
+        }
+        for chunk in self.chunks.values_mut() {
+            if chunk.dirty {
+                chunk.texture.update(&chunk.image);
+                chunk.dirty = false;
+            }
+        }
+    }
+
+    fn map_input(&mut self) {
+        let speed = MAP_PAN_SPEED / self.zoom;
+        if is_key_down(key(Action::PanLeft)) {
+            self.center.x -= speed;
+        }
+        if is_key_down(key(Action::PanRight)) {
+            self.center.x += speed;
+        }
+        if is_key_down(key(Action::PanUp)) {
+            self.center.y -= speed;
+        }
+        if is_key_down(key(Action::PanDown)) {
+            self.center.y += speed;
+        }
+
+        // zoom around whatever is under the mouse
+        let mouse = Vec2::from(mouse_position());
+        let (_, wheel_y) = mouse_wheel();
+        if wheel_y != 0.0 {
+            let before = self.screen_to_map(mouse);
+            let factor = if wheel_y > 0.0 { 1.25 } else { 0.8 };
+            self.zoom = (self.zoom * factor).clamp(MIN_MAP_ZOOM, MAX_MAP_ZOOM);
+            self.center += before - self.screen_to_map(mouse);
+        }
+
+        if is_mouse_button_down(MouseButton::Left) {
+            let under_mouse = self.screen_to_map(mouse);
+            match self.drag_anchor {
+                Some(anchor) => self.center += anchor - under_mouse,
+                None => self.drag_anchor = Some(under_mouse),
+            }
+        } else {
+            self.drag_anchor = None;
+        }
+    }
+
+    // screen pixels to blocks on the full map
+    fn screen_to_map(&self, screen: Vec2) -> Vec2 {
+        let half = vec2(screen_width(), screen_height()) / 2.0;
+        return (screen - half) / self.zoom + self.center;
+    }
+
+    fn map_to_screen(&self, x: f32, y: f32) -> Vec2 {
+        let half = vec2(screen_width(), screen_height()) / 2.0;
+        return (vec2(x, y) - self.center) * self.zoom + half;
+    }
+
+    // the corner minimap, with the default camera set. `entities` are positions
+    pub fn render_corner(&self, player_pos: SubGridPos, entities: &[SubGridPos]) {
+        let left = screen_width() - MINIMAP_SIZE - MINIMAP_MARGIN;
+        let top = MINIMAP_MARGIN;
+        draw_rectangle(
+            left,
+            top,
+            MINIMAP_SIZE,
+            MINIMAP_SIZE,
+            Color::new(0.0, 0.0, 0.0, 0.5),
+        );
+
+        // the blocks around the player that fit in the box
+        let range = MINIMAP_SIZE / MINIMAP_SCALE;
+        let view = Rect::new(
+            player_pos.x + 1.5 - range / 2.0,
+            player_pos.y + 1.5 - range / 2.0,
+            range,
+            range,
+        );
+        let first_chunk = chunk_of(view.x.floor() as i32);
+        let last_chunk = chunk_of(view.right().ceil() as i32);
+        for chunk_x in first_chunk..=last_chunk {
+            let Some(chunk) = self.chunks.get(&chunk_x) else {
+                continue;
+            };
+            let bounds = Rect::new(
+                (chunk_x * CHUNK_WIDTH) as f32,
+                SKY_LIMIT as f32,
+                CHUNK_WIDTH as f32,
+                MAP_HEIGHT as f32,
+            );
+            let Some(shown) = bounds.intersect(view) else {
+                continue;
+            };
+            draw_texture_ex(
+                &chunk.texture,
+                left + (shown.x - view.x) * MINIMAP_SCALE,
+                top + (shown.y - view.y) * MINIMAP_SCALE,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(shown.size() * MINIMAP_SCALE),
+                    source: Some(Rect::new(
+                        shown.x - bounds.x,
+                        shown.y - bounds.y,
+                        shown.w,
+                        shown.h,
+                    )),
+                    ..Default::default()
+                },
+            );
+        }
+
+        for pos in entities {
+            if view.contains(vec2(pos.x, pos.y)) {
+                let x = left + (pos.x - view.x) * MINIMAP_SCALE;
+                let y = top + (pos.y - view.y) * MINIMAP_SCALE;
+                draw_rectangle(x - 1.0, y - 1.0, 3.0, 3.0, YELLOW);
+            }
+        }
+        let player_x = left + (player_pos.x - view.x) * MINIMAP_SCALE;
+        let player_y = top + (player_pos.y - view.y) * MINIMAP_SCALE;
+        draw_rectangle(
+            player_x,
+            player_y,
+            3.0 * MINIMAP_SCALE,
+            3.0 * MINIMAP_SCALE,
+            WHITE,
+        );
+        draw_rectangle_lines(left, top, MINIMAP_SIZE, MINIMAP_SIZE, 1.0, WHITE);
+    }
+
+    // every explored chunk over the whole screen, with the default camera set
+    pub fn render_full(&self, player_pos: SubGridPos, entities: &[SubGridPos]) {
+        draw_rectangle(
+            0.0,
+            0.0,
+            screen_width(),
+            screen_height(),
+            Color::new(0.0, 0.0, 0.0, 0.85),
+        );
+        let chunk_size = vec2(CHUNK_WIDTH as f32, MAP_HEIGHT as f32) * self.zoom;
+        for (chunk_x, chunk) in &self.chunks {
+            let corner = self.map_to_screen((chunk_x * CHUNK_WIDTH) as f32, SKY_LIMIT as f32);
+            if corner.x > screen_width() || corner.x + chunk_size.x < 0.0 {
+                continue;
+            }
+            draw_texture_ex(
+                &chunk.texture,
+                corner.x,
+                corner.y,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(chunk_size),
+                    ..Default::default()
+                },
+            );
+        }
+
+        let marker = (self.zoom * 3.0).max(3.0);
+        for pos in entities {
+            let screen = self.map_to_screen(pos.x, pos.y);
+            draw_rectangle(screen.x - 1.0, screen.y - 1.0, 3.0, 3.0, YELLOW);
+        }
+        let player = self.map_to_screen(player_pos.x, player_pos.y);
+        draw_rectangle(player.x, player.y, marker, marker, WHITE);
+    }
+}
+
+fn build_chunk(chunk_x: i32, blocks: &HashMap<GridPos, Block>) -> MapChunk {
+    let mut image = Image::gen_image_color(CHUNK_WIDTH as u16, MAP_HEIGHT as u16, BLANK);
+    for y in SKY_LIMIT..WORLD_BOTTOM {
+        for local_x in 0..CHUNK_WIDTH {
+            let color = cell_color(blocks, chunk_x * CHUNK_WIDTH + local_x, y);
+            image.set_pixel(local_x as u32, (y - SKY_LIMIT) as u32, color);
+        }
+    }
+    let texture = Texture2D::from_image(&image);
+    texture.set_filter(FilterMode::Nearest);
+    return MapChunk {
+        image,
+        texture,
+        dirty: false,
+    };
+}
+
+// the foreground block, or the darker background block behind an empty cell
+fn cell_color(blocks: &HashMap<GridPos, Block>, x: i32, y: i32) -> Color {
+    if let Some(block) = blocks.get(&GridPos::new(x, y, false)) {
+        return Color::new(block.color.r, block.color.g, block.color.b, 1.0);
+    }
+    if let Some(block) = blocks.get(&GridPos::new(x, y, true)) {
+        return Color::new(
+            block.color.r * BG_SHADE,
+            block.color.g * BG_SHADE,
+            block.color.b * BG_SHADE,
+            1.0,
+        );
+    }
+    return BLANK;
+}
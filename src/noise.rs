@@ -0,0 +1,76 @@
+// small deterministic value noise, everything is derived from the seed so
+// the same seed always produces the same world
+
+pub fn hash(seed: u32, x: i32, y: i32) -> u32 {
+    let mut h = seed ^ (x as u32).wrapping_mul(0x27d4_eb2d) ^ (y as u32).wrapping_mul(0x1656_67b1);
+    h = (h ^ (h >> 15)).wrapping_mul(0x2c1b_3c6d);
+    h = (h ^ (h >> 12)).wrapping_mul(0x297a_2d39);
+    return h ^ (h >> 15);
+}
+
+// random value in 0..1 for a single lattice point
+pub fn rand_unit(seed: u32, x: i32, y: i32) -> f32 {
+    return hash(seed, x, y) as f32 / u32::MAX as f32;
+}
+
+fn smooth(t: f32) -> f32 {
+    return t * t * (3.0 - 2.0 * t);
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    return a + (b - a) * t;
+}
+
+pub fn value_noise_1d(seed: u32, x: f32) -> f32 {
+    let x0 = x.floor();
+    let t = smooth(x - x0);
+    let a = rand_unit(seed, x0 as i32, 0);
+    let b = rand_unit(seed, x0 as i32 + 1, 0);
+    return lerp(a, b, t);
+}
+
+pub fn value_noise_2d(seed: u32, x: f32, y: f32) -> f32 {
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let tx = smooth(x - x0);
+    let ty = smooth(y - y0);
+    let (xi, yi) = (x0 as i32, y0 as i32);
+    let top = lerp(rand_unit(seed, xi, yi), rand_unit(seed, xi + 1, yi), tx);
+    let bottom = lerp(
+        rand_unit(seed, xi, yi + 1),
+        rand_unit(seed, xi + 1, yi + 1),
+        tx,
+    );
+    return lerp(top, bottom, ty);
+}
+
+// layered noise, each octave doubles the frequency and halves the amplitude.
+// result is normalized back to 0..1
+pub fn fractal_noise_1d(seed: u32, x: f32, octaves: u32) -> f32 {
+    let mut total = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut max = 0.0;
+    for octave in 0..octaves {
+        total += value_noise_1d(seed.wrapping_add(octave), x * frequency) * amplitude;
+        max += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    return total / max;
+}
+
+pub fn fractal_noise_2d(seed: u32, x: f32, y: f32, octaves: u32) -> f32 {
+    let mut total = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut max = 0.0;
+    for octave in 0..octaves {
+        total +=
+            value_noise_2d(seed.wrapping_add(octave), x * frequency, y * frequency) * amplitude;
+        max += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    return total / max;
+}
@@ -0,0 +1,183 @@
+use crate::atlas::atlas;
+use crate::atlas::has_atlas_image;
+use crate::noise::fractal_noise_1d;
+use crate::terrain::SURFACE_LEVEL;
+use crate::utils::hex_color;
+use crate::utils::is_hex_color;
+use crate::vis::VisRange;
+use crate::BLOCK_SIZE;
+use macroquad::prelude::*;
+
+const PARALLAX_SEED: u32 = 0x27d4_eb2f;
+// width of the strips silhouettes are drawn with, in world pixels
+const STRIP_WIDTH: f32 = BLOCK_SIZE / 2.0;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum LayerShape {
+    // soft rolling silhouette
+    Hills,
+    // ridged silhouette with sharp peaks
+    Mountains,
+    // an atlas image repeated side by side
+    Image(String),
+}
+
+pub struct ParallaxLayer {
+    pub name: String,
+    pub shape: LayerShape,
+    // 0 stays fixed on screen, 1 moves with the world
+    pub factor: f32,
+    pub color: Color,
+    // all in blocks
+    pub base: f32,
+    pub height: f32,
+    pub scale: f32,
+}
+
+impl ParallaxLayer {
+    // top of the silhouette at a layer column, in blocks above the base
+    fn height_at(&self, seed: u32, column: f32) -> f32 {
+        match self.shape {
+            LayerShape::Hills => fractal_noise_1d(seed, column / self.scale, 2) * self.height,
+            LayerShape::Mountains => {
+                let noise = fractal_noise_1d(seed, column / self.scale, 3);
+                // fold the noise so every crest becomes a sharp ridge
+                let ridge = 1.0 - (noise * 2.0 - 1.0).abs();
+                ridge * ridge * self.height
+            }
+            LayerShape::Image(_) => 0.0,
+        }
+    }
+}
+
+// the stack of layers between the sky and the world
+pub struct Parallax {
+    pub layers: Vec<ParallaxLayer>,
+    seed: u32,
+}
+
+impl Parallax {
+    pub fn new(layers: Vec<ParallaxLayer>, seed: u32) -> Self {
+        Parallax { layers, seed }
+    }
+
+    // layer format is described at the top of assets/parallax.txt
+    pub fn parse(source: &str) -> Result<Vec<ParallaxLayer>, String> {
+        let mut layers: Vec<ParallaxLayer> = Vec::new();
+        for line in source.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .ok_or(format!("expected `key: value`, got `{}`", line))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            if key == "layer" {
+                layers.push(ParallaxLayer {
+                    name: value.to_owned(),
+                    shape: LayerShape::Hills,
+                    factor: 0.5,
+                    color: hex_color("#15171c", 1.0),
+                    base: 0.0,
+                    height: 8.0,
+                    scale: 16.0,
+                });
+                continue;
+            }
+            let layer = layers
+                .last_mut()
+                .ok_or(format!("`{}` comes before the first layer", key))?;
+            let number = || {
+                value
+                    .parse::<f32>()
+                    .map_err(|_| format!("invalid {} `{}`", key, value))
+            };
+            match key {
+                "shape" => {
+                    layer.shape = match value.split_once(' ') {
+                        Some(("image", name)) => {
+                            let name = name.trim();
+                            if !has_atlas_image(name) {
+                                return Err(format!("`{}` is not in the texture atlas", name));
+                            }
+                            LayerShape::Image(name.to_owned())
+                        }
+                        _ if value == "hills" => LayerShape::Hills,
+                        _ if value == "mountains" => LayerShape::Mountains,
+                        _ => return Err(format!("unknown shape `{}`", value)),
+                    }
+                }
+                "factor" => layer.factor = number()?,
+                "color" => {
+                    if !is_hex_color(value) {
+                        return Err(format!("invalid color `{}`", value));
+                    }
+                    layer.color = hex_color(value, 1.0);
+                }
+                "base" => layer.base = number()?,
+                "height" => layer.height = number()?,
+                "scale" => {
+                    layer.scale = number()?;
+                    if layer.scale <= 0.0 {
+                        return Err(format!("layer `{}` needs a positive scale", layer.name));
+                    }
+                }
+                other => return Err(format!("unknown key `{}`", other)),
+            }
+        }
+        return Ok(layers);
+    }
+
+    // draws every layer with the world camera set. `center` is what the camera is
+    // looking at and `ambient` tints the layers for the time of day
+    pub fn render(&self, view: &VisRange, center: Vec2, ambient: Color) {
+        let horizon = SURFACE_LEVEL as f32 * BLOCK_SIZE;
+        for (index, layer) in self.layers.iter().enumerate() {
+            let seed = self.seed ^ PARALLAX_SEED ^ (index as u32).wrapping_mul(0x9e37_79b9);
+            let color = Color::new(
+                layer.color.r * ambient.r,
+                layer.color.g * ambient.g,
+                layer.color.b * ambient.b,
+                layer.color.a,
+            );
+            // world position of the layer's origin, it moves `1 - factor` as fast as
+            // the camera so it appears to scroll `factor` as fast as the world
+            let origin_x = center.x * (1.0 - layer.factor);
+            let base_y = center.y + (horizon - center.y) * layer.factor - layer.base * BLOCK_SIZE;
+
+            if let LayerShape::Image(name) = &layer.shape {
+                let region = atlas().region(name);
+                let first = ((view.min_x - origin_x) / region.rect.w).floor() as i32;
+                let last = ((view.max_x - origin_x) / region.rect.w).ceil() as i32;
+                for tile in first..=last {
+                    atlas().draw(
+                        name,
+                        origin_x + tile as f32 * region.rect.w,
+                        base_y - region.rect.h,
+                        color,
+                        DrawTextureParams::default(),
+                    );
+                }
+                continue;
+            }
+
+            // vertical strips from the silhouette down to the bottom of the screen
+            let mut x = ((view.min_x - origin_x) / STRIP_WIDTH).floor() * STRIP_WIDTH + origin_x;
+            while x < view.max_x {
+                let column = (x - origin_x) / BLOCK_SIZE;
+                let top = base_y - layer.height_at(seed, column) * BLOCK_SIZE;
+                if top < view.max_y {
+                    draw_rectangle(x, top, STRIP_WIDTH, view.max_y - top, color);
+                }
+                x += STRIP_WIDTH;
+            }
+        }
+    }
+}
+
+pub fn load_parallax(seed: u32) -> Parallax {
+    let layers = Parallax::parse(include_str!("./assets/parallax.txt")).unwrap();
+    return Parallax::new(layers, seed);
+}
@@ -0,0 +1,308 @@
+use crate::block::Block;
+use crate::block::BlockType;
+use crate::position::GridPos;
+use crate::vis::VisRange;
+use crate::BLOCK_SIZE;
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+use std::collections::HashMap;
+
+// never more particles than this alive at once, new ones are dropped when full
+const MAX_PARTICLES: usize = 8000;
+// how many frames a particle takes to fade out at the end of its life
+const FADE_FRAMES: u32 = 20;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ParticleKind {
+    // bits of a broken block, bounce off the ground
+    Debris,
+    // puffs kicked up when landing, float and don't collide
+    Dust,
+    // droplets thrown up by liquids, gone when they hit something
+    Splash,
+    // glowing sparks rising from lava
+    Ember,
+    Rain,
+    Snow,
+}
+
+impl ParticleKind {
+    // added to vel_y every frame, negative floats up
+    fn gravity(&self) -> f32 {
+        match self {
+            ParticleKind::Debris | ParticleKind::Splash => 0.02,
+            ParticleKind::Dust => -0.002,
+            ParticleKind::Ember => -0.004,
+            ParticleKind::Rain | ParticleKind::Snow => 0.0,
+        }
+    }
+
+    // velocity kept every frame
+    fn drag(&self) -> f32 {
+        match self {
+            ParticleKind::Dust => 0.9,
+            ParticleKind::Ember => 0.97,
+            ParticleKind::Rain | ParticleKind::Snow => 1.0,
+            _ => 0.99,
+        }
+    }
+
+    // weather is blown around, `wind` is multiplied by this
+    fn wind_factor(&self) -> f32 {
+        match self {
+            ParticleKind::Rain => 30.0,
+            ParticleKind::Snow => 20.0,
+            ParticleKind::Dust | ParticleKind::Ember => 5.0,
+            _ => 0.0,
+        }
+    }
+
+    fn collides_with(&self, block: &Block) -> bool {
+        match self {
+            ParticleKind::Dust | ParticleKind::Ember => false,
+            // flakes stop on snow that can still get thicker
+            ParticleKind::Snow if block.block_type == BlockType::Snow => block.level < 1.0,
+            // weather lands on anything but torches and snow, including liquids
+            ParticleKind::Rain | ParticleKind::Snow => block.block_type.light_cost() > 1,
+            _ => block.block_type.is_solid(),
+        }
+    }
+
+    // drawn at full brightness no matter how dark it is around them
+    fn is_emissive(&self) -> bool {
+        return *self == ParticleKind::Ember;
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Particle {
+    pub kind: ParticleKind,
+    // grid units, velocities are per frame like the player's
+    pub x: f32,
+    pub y: f32,
+    pub vel_x: f32,
+    pub vel_y: f32,
+    pub color: Color,
+    // in pixels
+    pub size: f32,
+    // frames left to live
+    pub life: u32,
+}
+
+// a particle ran into a block. `from` is the cell it was in the frame before,
+// so the top of whatever it hit is `from` when it came straight down
+pub struct ParticleHit {
+    pub kind: ParticleKind,
+    pub cell: GridPos,
+    pub from: GridPos,
+}
+
+// every particle in the world. storage is allocated once up front and dead
+// particles are swapped out, so bursts of thousands don't allocate
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+}
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        ParticleSystem {
+            particles: Vec::with_capacity(MAX_PARTICLES),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        return self.particles.len();
+    }
+
+    pub fn is_full(&self) -> bool {
+        return self.particles.len() >= MAX_PARTICLES;
+    }
+
+    pub fn spawn(&mut self, particle: Particle) {
+        if !self.is_full() {
+            self.particles.push(particle);
+        }
+    }
+
+    // a broken block flies apart into bits of its own color
+    pub fn emit_debris(&mut self, pos: GridPos, color: Color) {
+        for _ in 0..12 {
+            let mut color = color;
+            // vary the shade so the bits don't blend into one blob
+            let shade = gen_range(0.75, 1.1);
+            color.r *= shade;
+            color.g *= shade;
+            color.b *= shade;
+            self.spawn(Particle {
+                kind: ParticleKind::Debris,
+                x: pos.x as f32 + gen_range(0.1, 0.9),
+                y: pos.y as f32 + gen_range(0.1, 0.9),
+                vel_x: gen_range(-0.12, 0.12),
+                vel_y: gen_range(-0.3, -0.05),
+                color,
+                size: gen_range(1.0, 2.0),
+                life: gen_range(60, 100),
+            });
+        }
+    }
+
+    // puff at the player's feet, `strength` is how hard they landed
+    pub fn emit_dust(&mut self, x: f32, y: f32, width: f32, strength: f32) {
+        let count = (strength * 20.0) as usize;
+        for _ in 0..count {
+            let side = if gen_range(0, 2) == 0 { -1.0 } else { 1.0 };
+            self.spawn(Particle {
+                kind: ParticleKind::Dust,
+                x: x + gen_range(0.0, width),
+                y: y - gen_range(0.0, 0.3),
+                vel_x: side * gen_range(0.02, 0.08) * strength * 2.0,
+                vel_y: gen_range(-0.04, 0.0),
+                color: Color::new(0.75, 0.72, 0.66, 0.6),
+                size: gen_range(1.0, 2.5),
+                life: gen_range(20, 40),
+            });
+        }
+    }
+
+    pub fn emit_splash(&mut self, x: f32, y: f32, color: Color, count: usize) {
+        for _ in 0..count {
+            self.spawn(Particle {
+                kind: ParticleKind::Splash,
+                x: x + gen_range(-0.3, 0.3),
+                y: y - 0.05,
+                vel_x: gen_range(-0.08, 0.08),
+                vel_y: gen_range(-0.25, -0.08),
+                color,
+                size: 1.0,
+                life: 40,
+            });
+        }
+    }
+
+    pub fn emit_ember(&mut self, pos: GridPos) {
+        self.spawn(Particle {
+            kind: ParticleKind::Ember,
+            x: pos.x as f32 + gen_range(0.0, 1.0),
+            y: pos.y as f32,
+            vel_x: gen_range(-0.02, 0.02),
+            vel_y: gen_range(-0.08, -0.03),
+            color: Color::new(1.0, gen_range(0.4, 0.7), 0.15, 1.0),
+            size: 1.0,
+            life: gen_range(60, 120),
+        });
+    }
+
+    // moves everything one frame and returns what ran into blocks
+    pub fn update(&mut self, blocks: &HashMap<GridPos, Block>, wind: f32) -> Vec<ParticleHit> {
+        let mut hits: Vec<ParticleHit> = Vec::new();
+        let mut index = 0;
+        while index < self.particles.len() {
+            let particle = &mut self.particles[index];
+            if particle.life == 0 || !step(particle, blocks, wind, &mut hits) {
+                self.particles.swap_remove(index);
+                continue;
+            }
+            particle.life -= 1;
+            index += 1;
+        }
+
+        // rain bursts into droplets where it lands
+        for hit in &hits {
+            if hit.kind == ParticleKind::Rain {
+                let color = Color::new(0.6, 0.7, 0.85, 0.6);
+                let x = hit.cell.x as f32 + 0.5;
+                self.emit_splash(x, hit.cell.y as f32, color, 2);
+            }
+        }
+        return hits;
+    }
+
+    // draws what's inside `view`. `light` is how lit a cell is, like World::block_light
+    pub fn render(&self, view: &VisRange, light: impl Fn(GridPos) -> Color) {
+        for particle in &self.particles {
+            if !view.contains_coord(particle.x * BLOCK_SIZE, particle.y * BLOCK_SIZE) {
+                continue;
+            }
+            let mut color = particle.color;
+            if !particle.kind.is_emissive() {
+                let cell =
+                    GridPos::new(particle.x.floor() as i32, particle.y.floor() as i32, false);
+                let light = light(cell);
+                color.r *= light.r;
+                color.g *= light.g;
+                color.b *= light.b;
+            }
+            if particle.life < FADE_FRAMES {
+                color.a *= particle.life as f32 / FADE_FRAMES as f32;
+            }
+
+            let x = particle.x * BLOCK_SIZE;
+            let y = particle.y * BLOCK_SIZE;
+            if particle.kind == ParticleKind::Rain {
+                // streak back along the way it came
+                let tail_x = x - particle.vel_x * BLOCK_SIZE;
+                let tail_y = y - particle.vel_y * BLOCK_SIZE;
+                draw_line(tail_x, tail_y, x, y, 0.5, color);
+            } else {
+                let half = particle.size / 2.0;
+                draw_rectangle(x - half, y - half, particle.size, particle.size, color);
+            }
+        }
+    }
+}
+
+// one frame of movement, returns false once the particle is gone
+fn step(
+    particle: &mut Particle,
+    blocks: &HashMap<GridPos, Block>,
+    wind: f32,
+    hits: &mut Vec<ParticleHit>,
+) -> bool {
+    let kind = particle.kind;
+    particle.vel_y += kind.gravity();
+    particle.vel_x *= kind.drag();
+    particle.vel_y *= kind.drag();
+    let mut vel_x = particle.vel_x + wind * kind.wind_factor();
+    if kind == ParticleKind::Snow {
+        // flakes wobble on their way down
+        vel_x += gen_range(-0.05, 0.05);
+    }
+
+    let cell_at = |x: f32, y: f32| GridPos::new(x.floor() as i32, y.floor() as i32, false);
+    let blocked = |x: f32, y: f32| {
+        blocks
+            .get(&cell_at(x, y))
+            .is_some_and(|block| kind.collides_with(block))
+    };
+    let from = cell_at(particle.x, particle.y);
+    let new_x = particle.x + vel_x;
+    let new_y = particle.y + particle.vel_y;
+
+    if kind != ParticleKind::Debris {
+        particle.x = new_x;
+        particle.y = new_y;
+        if blocked(new_x, new_y) {
+            hits.push(ParticleHit {
+                kind,
+                cell: cell_at(new_x, new_y),
+                from,
+            });
+            return false;
+        }
+        return true;
+    }
+
+    // debris bounces, one axis at a time like the player
+    if blocked(new_x, particle.y) {
+        particle.vel_x *= -0.3;
+    } else {
+        particle.x = new_x;
+    }
+    if blocked(particle.x, new_y) {
+        particle.vel_y *= -0.3;
+        particle.vel_x *= 0.7;
+    } else {
+        particle.y = new_y;
+    }
+    return true;
+}
@@ -0,0 +1,214 @@
+use crate::block::Block;
+use crate::block::BlockOverlay;
+use crate::block::BlockType;
+use crate::position::GridPos;
+use crate::terrain::WORLD_BOTTOM;
+use crate::utils::hex_color;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// thinnest a puddle is allowed to get, liquids won't spread any further than this
+const MIN_LEVEL: f32 = 0.1;
+// level differences smaller than this are considered settled
+const SETTLE_LEVEL: f32 = 0.01;
+
+// cellular update for granular and liquid blocks. only cells next to something that
+// changed are looked at, everything else is asleep so a big static world costs nothing
+pub struct BlockPhysics {
+    active: HashSet<GridPos>,
+    tick: u32,
+}
+
+impl BlockPhysics {
+    pub fn new() -> Self {
+        BlockPhysics {
+            active: HashSet::new(),
+            tick: 0,
+        }
+    }
+
+    // something changed at pos, it and its neighbours may be able to move now
+    pub fn wake(&mut self, pos: GridPos) {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                self.active
+                    .insert(GridPos::new(pos.x + dx, pos.y + dy, pos.bg));
+            }
+        }
+    }
+
+    pub fn active_count(&self) -> usize {
+        return self.active.len();
+    }
+
+    // runs one step and returns every cell that changed. moved blocks are not woken
+    // here, the caller is expected to report changes back through `wake`
+    pub fn update(&mut self, blocks: &mut HashMap<GridPos, Block>) -> Vec<GridPos> {
+        self.tick = self.tick.wrapping_add(1);
+        let mut cells: Vec<GridPos> = self.active.drain().collect();
+        // bottom rows first so a whole column falls together
+        cells.sort_by_key(|pos| -pos.y);
+
+        let mut changed: Vec<GridPos> = Vec::new();
+        for pos in cells {
+            let Some(block) = blocks.get(&pos).copied() else {
+                continue;
+            };
+            if block.block_type.is_granular() {
+                self.update_granular(pos, block, blocks, &mut changed);
+            } else if block.block_type.is_liquid() {
+                // thick liquids skip ticks but have to stay awake meanwhile
+                if !self.tick.is_multiple_of(block.block_type.flow_interval()) {
+                    self.active.insert(pos);
+                    continue;
+                }
+                update_liquid(pos, block, blocks, &mut changed);
+            }
+        }
+        return changed;
+    }
+
+    fn update_granular(
+        &self,
+        pos: GridPos,
+        block: Block,
+        blocks: &mut HashMap<GridPos, Block>,
+        changed: &mut Vec<GridPos>,
+    ) {
+        let Some(target) = self.find_move(pos, block, blocks) else {
+            return;
+        };
+        // sinking into a liquid pushes the liquid up into our old cell
+        match blocks.insert(target, block) {
+            Some(displaced) => blocks.insert(pos, displaced),
+            None => blocks.remove(&pos),
+        };
+        changed.push(pos);
+        changed.push(target);
+    }
+
+    fn find_move(
+        &self,
+        pos: GridPos,
+        block: Block,
+        blocks: &HashMap<GridPos, Block>,
+    ) -> Option<GridPos> {
+        // the bottom of the world holds everything up
+        if pos.y >= WORLD_BOTTOM - 1 {
+            return None;
+        }
+        let free = |x: i32, y: i32| match blocks.get(&GridPos::new(x, y, pos.bg)) {
+            Some(other) => other.block_type.is_liquid(),
+            None => true,
+        };
+
+        if free(pos.x, pos.y + 1) {
+            return Some(GridPos::new(pos.x, pos.y + 1, pos.bg));
+        }
+
+        // alternate which side is tried first so piles grow evenly
+        let sides = if (self.tick as i32 + pos.x) % 2 == 0 {
+            [-1, 1]
+        } else {
+            [1, -1]
+        };
+        let drop = block.block_type.slide_drop();
+        for dx in sides {
+            let x = pos.x + dx;
+            if free(x, pos.y) && (1..=drop).all(|dy| free(x, pos.y + dy)) {
+                return Some(GridPos::new(x, pos.y + 1, pos.bg));
+            }
+        }
+        return None;
+    }
+}
+
+fn update_liquid(
+    pos: GridPos,
+    mut block: Block,
+    blocks: &mut HashMap<GridPos, Block>,
+    changed: &mut Vec<GridPos>,
+) {
+    let kind = block.block_type;
+
+    // lava touching water cools down into stone
+    if kind == BlockType::Lava {
+        let touches_water = [(0, -1), (-1, 0), (1, 0), (0, 1)].iter().any(|(dx, dy)| {
+            blocks
+                .get(&GridPos::new(pos.x + dx, pos.y + dy, pos.bg))
+                .is_some_and(|other| other.block_type == BlockType::Water)
+        });
+        if touches_water {
+            let stone = hex_color("#2b2d33", 1.0);
+            blocks.insert(pos, Block::new(BlockType::Solid, stone, BlockOverlay::None));
+            changed.push(pos);
+            return;
+        }
+    }
+
+    // fall into the cell below as far as it has room
+    let below = GridPos::new(pos.x, pos.y + 1, pos.bg);
+    if pos.y < WORLD_BOTTOM - 1 {
+        match blocks.get(&below).copied() {
+            None => {
+                blocks.remove(&pos);
+                blocks.insert(below, block);
+                changed.push(pos);
+                changed.push(below);
+                return;
+            }
+            Some(mut other) if other.block_type == kind && other.level < 1.0 => {
+                let amount = block.level.min(1.0 - other.level);
+                other.level += amount;
+                block.level -= amount;
+                blocks.insert(below, other);
+                changed.push(below);
+                if block.level < SETTLE_LEVEL {
+                    blocks.remove(&pos);
+                    changed.push(pos);
+                    return;
+                }
+                blocks.insert(pos, block);
+                changed.push(pos);
+            }
+            _ => {}
+        }
+    }
+
+    // level out with the neighbours on either side
+    let mut targets: Vec<(GridPos, f32)> = Vec::new();
+    for dx in [-1, 1] {
+        let side = GridPos::new(pos.x + dx, pos.y, pos.bg);
+        match blocks.get(&side) {
+            None => targets.push((side, 0.0)),
+            Some(other) if other.block_type == kind && other.level < block.level => {
+                targets.push((side, other.level))
+            }
+            _ => {}
+        }
+    }
+    // only spill into empty cells if the puddle stays thick enough
+    let mut total = block.level + targets.iter().map(|(_, level)| level).sum::<f32>();
+    let mut average = total / (targets.len() + 1) as f32;
+    if average < MIN_LEVEL {
+        targets.retain(|(_, level)| *level > 0.0);
+        total = block.level + targets.iter().map(|(_, level)| level).sum::<f32>();
+        average = total / (targets.len() + 1) as f32;
+    }
+    if targets.is_empty() || block.level - average < SETTLE_LEVEL {
+        return;
+    }
+
+    for (side, _) in targets {
+        let mut spread = match blocks.get(&side) {
+            Some(other) => *other,
+            None => Block::new(kind, block.color, BlockOverlay::None),
+        };
+        spread.level = average;
+        blocks.insert(side, spread);
+        changed.push(side);
+    }
+    block.level = average;
+    blocks.insert(pos, block);
+    changed.push(pos);
+}
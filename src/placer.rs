@@ -1,6 +1,9 @@
 use crate::{
     block::{render_block, render_block_overlay, Block, BlockOverlay, BlockType},
     position::GridPos,
+    settings::{key, Action},
+    ui::{Anchor, Ui},
+    world::inside_world,
     BLOCK_SIZE,
 };
 use hsl::HSL;
@@ -13,9 +16,17 @@ pub struct Placer {
     color: f64,
     brightness: f64,
     overlay: BlockOverlay,
+    block_type: BlockType,
 }
 
-const COLOR_HUD_WIDTH: f32 = 5.0;
+// the hud shows this many hues or brightnesses across
+const HUD_STEPS: i32 = 36;
+const HUD_WIDTH: f32 = 180.0;
+// how far one wheel notch moves the hue, in degrees, and the brightness
+const HUE_STEP: f64 = 20.0;
+const BRIGHTNESS_STEP: f64 = 0.025;
+// where the brightness starts, the wheel only reaches steps away from it
+const DEFAULT_BRIGHTNESS: f64 = 0.67;
 
 impl Placer {
     pub fn new() -> Self {
@@ -23,8 +34,9 @@ impl Placer {
             last_x: 0.0,
             last_y: 0.0,
             color: 0.0,
-            brightness: 0.67,
+            brightness: DEFAULT_BRIGHTNESS,
             overlay: BlockOverlay::None,
+            block_type: BlockType::Solid,
         }
     }
 
@@ -37,7 +49,39 @@ impl Placer {
         .to_rgb();
         return Color::from_rgba(hsl_color.0, hsl_color.1, hsl_color.2, 255);
     }
-    pub fn update(&mut self, camera: &Camera2D, blocks: &mut HashMap<GridPos, Block>) {
+    pub fn get_block_type(&self) -> BlockType {
+        return self.block_type;
+    }
+
+    pub fn set_block_type(&mut self, block_type: BlockType) {
+        self.block_type = block_type;
+    }
+
+    // picks up the hue and brightness of `color`, like middle clicking a block
+    pub fn set_color(&mut self, color: Color) {
+        let color = HSL::from_rgb(&[
+            (color.r * 255.0) as u8,
+            (color.g * 255.0) as u8,
+            (color.b * 255.0) as u8,
+        ]);
+        self.color = color.h;
+        self.brightness = color.l;
+    }
+
+    // the wheel changes color or brightness while a modifier is held
+    pub fn uses_mouse_wheel(&self) -> bool {
+        return is_key_down(key(Action::ColorWheel)) || is_key_down(key(Action::BrightnessWheel));
+    }
+
+    // returns the cell to change this frame, if any, along with the block that goes
+    // in it or None to remove it. the world makes the change. `pick` lets the
+    // middle button pick up the hovered block's color
+    pub fn update(
+        &mut self,
+        camera: &Camera2D,
+        blocks: &HashMap<GridPos, Block>,
+        pick: bool,
+    ) -> Option<(GridPos, Option<Block>)> {
         self.placer_input_update();
 
         // get mouse pos in world space
@@ -51,21 +95,26 @@ impl Placer {
             block_pos.y.floor() as i32,
             false,
         );
-        if blocks.contains_key(&block_grid_pos) {
-            self.select_loop(block_pos, block_grid_pos, blocks);
-        } else {
-            self.place_loop(block_pos, block_grid_pos, blocks);
+        if !inside_world(block_grid_pos) {
+            return None;
         }
+        let changed = if blocks.contains_key(&block_grid_pos) {
+            self.select_loop(block_pos, block_grid_pos, blocks, pick)
+        } else {
+            self.place_loop(block_pos, block_grid_pos)
+        };
         self.last_x = block_pos.x;
         self.last_y = block_pos.y;
+        return changed;
     }
 
     fn select_loop(
         &mut self,
         _: Vec2,
         block_grid_pos: GridPos,
-        blocks: &mut HashMap<GridPos, Block>,
-    ) {
+        blocks: &HashMap<GridPos, Block>,
+        pick: bool,
+    ) -> Option<(GridPos, Option<Block>)> {
         // let block = blocks.get(&block_grid_pos).unwrap();
 
         let screen_grid_x = block_grid_pos.x as f32 * BLOCK_SIZE;
@@ -81,38 +130,29 @@ impl Placer {
 
         if is_mouse_button_down(MouseButton::Right) {
             if block_grid_pos.x as f32 == self.last_x && block_grid_pos.y as f32 == self.last_y {
-                return;
+                return None;
             }
-            blocks.remove(&block_grid_pos);
-        } else if is_mouse_button_down(MouseButton::Middle) {
+            return Some((block_grid_pos, None));
+        } else if pick && is_mouse_button_down(MouseButton::Middle) {
             if let Some(block) = blocks.get(&block_grid_pos) {
-                let color = HSL::from_rgb(&[
-                    (block.color.r * 255.0) as u8,
-                    (block.color.g * 255.0) as u8,
-                    (block.color.b * 255.0) as u8,
-                ]);
-                self.color = color.h;
-                self.brightness = color.l;
+                self.set_color(block.color);
                 self.overlay = block.overlay;
+                self.block_type = block.block_type;
             }
         }
+        return None;
     }
 
-    fn place_loop(
-        &mut self,
-        _: Vec2,
-        block_grid_pos: GridPos,
-        blocks: &mut HashMap<GridPos, Block>,
-    ) {
+    fn place_loop(&mut self, _: Vec2, block_grid_pos: GridPos) -> Option<(GridPos, Option<Block>)> {
         // calculate the correct color using hsl
         let color = self.calc_hsl_color();
 
         // we want to draw it transparently so clone the block
-        let mut block = Block::new(BlockType::Solid, color, self.overlay);
+        let mut block = Block::new(self.block_type, color, self.overlay);
         block.color.a = 0.5;
-        render_block(block, block_grid_pos);
+        render_block(block, block_grid_pos, WHITE);
         if self.overlay != BlockOverlay::None {
-            render_block_overlay(block, block_grid_pos);
+            render_block_overlay(block, block_grid_pos, WHITE);
         }
         block.color.a = 1.0;
 
@@ -120,108 +160,91 @@ impl Placer {
         if is_mouse_button_down(MouseButton::Left) {
             // todo: down? up?
             if block_grid_pos.x as f32 == self.last_x && block_grid_pos.y as f32 == self.last_y {
-                return;
+                return None;
             }
-            blocks.insert(block_grid_pos, block.clone());
+            return Some((block_grid_pos, Some(block)));
         }
+        return None;
     }
 
     fn placer_input_update(&mut self) {
         let (_, mouse_wheel_y) = mouse_wheel();
-        let color_modifier = is_key_down(KeyCode::LeftControl);
-        let bright_modifier = is_key_down(KeyCode::LeftAlt);
+        let color_modifier = is_key_down(key(Action::ColorWheel));
+        let bright_modifier = is_key_down(key(Action::BrightnessWheel));
 
         if mouse_wheel_y != 0.0 {
             if color_modifier {
-                self.color += 20.0 * -mouse_wheel_y.signum() as f64;
+                self.color += HUE_STEP * -mouse_wheel_y.signum() as f64;
             }
 
             if bright_modifier {
-                self.brightness += 0.025 * -mouse_wheel_y.signum() as f64;
+                self.brightness += BRIGHTNESS_STEP * -mouse_wheel_y.signum() as f64;
             }
         }
 
+        if is_key_pressed(key(Action::NextBlock)) {
+            self.block_type = self.block_type.next();
+        }
+
         self.color = (self.color + 360.0) % 360.0;
         self.brightness = clamp(self.brightness, 0.0, 1.0);
     }
 
-    pub fn render_hud(&self) {
-        if is_key_down(KeyCode::LeftControl) {
-            self.render_color_hud();
-        } else if is_key_down(KeyCode::LeftAlt) {
-            self.render_brightness_hud();
+    // the hue or brightness picker while the wheel is changing it
+    pub fn render_hud(&self, ui: &mut Ui) {
+        if is_key_down(key(Action::ColorWheel)) {
+            self.render_color_hud(ui);
+        } else if is_key_down(key(Action::BrightnessWheel)) {
+            self.render_brightness_hud(ui);
         }
     }
 
-    pub fn render_color_hud(&self) {
-        let estimated_size = COLOR_HUD_WIDTH * 36.0;
-        let start_x = screen_width() / 2.0 - estimated_size / 2.0;
-
-        for i in 0..36 {
-            let hsl_color = HSL {
-                h: (i * 10) as f64,
-                s: 0.67,
-                l: 0.67,
-            }
-            .to_rgb();
-            let color = Color::from_rgba(hsl_color.0, hsl_color.1, hsl_color.2, 255);
-            draw_rectangle(
-                start_x + ((i as f32 * COLOR_HUD_WIDTH) as f32),
-                8.0,
-                COLOR_HUD_WIDTH,
-                16.0,
-                color,
-            );
-        }
-
-        // preview rectangle
-        let color = self.calc_hsl_color();
-        draw_rectangle(start_x as f32, 32.0, estimated_size, 16.0, color);
-
-        // draw selected color
-        let estimated_pos = start_x + ((self.color as f32 / 10.0) * COLOR_HUD_WIDTH);
-        draw_rectangle(
-            estimated_pos,
-            8.0,
-            COLOR_HUD_WIDTH,
-            16.0,
-            Color::new(1.0, 1.0, 1.0, 0.5),
-        );
+    fn render_color_hud(&self, ui: &mut Ui) {
+        let hues: Vec<Color> = (0..HUD_STEPS)
+            .map(|i| hsl_color((i * 10) as f64, 0.67, 0.67))
+            .collect();
+        ui.begin_panel("placer", Anchor::Top, HUD_WIDTH);
+        ui.color_bar(&hues, (self.color / 350.0) as f32);
+        ui.color_swatch(self.calc_hsl_color());
+        ui.end_panel("placer");
     }
 
-    pub fn render_brightness_hud(&self) {
-        let estimated_size = COLOR_HUD_WIDTH * 36.0;
-        let start_x = screen_width() / 2.0 - estimated_size / 2.0;
-
-        for i in 0..36 {
-            let hsl_color = HSL {
-                h: self.color,
-                s: (i as f64 / 35.0),
-                l: (i as f64 / 35.0),
-            }
-            .to_rgb();
-            let color = Color::from_rgba(hsl_color.0, hsl_color.1, hsl_color.2, 255);
-            draw_rectangle(
-                start_x + ((i as f32 * COLOR_HUD_WIDTH) as f32),
-                8.0,
-                COLOR_HUD_WIDTH,
-                16.0,
-                color,
-            );
-        }
+    fn render_brightness_hud(&self, ui: &mut Ui) {
+        let shades: Vec<Color> = (0..HUD_STEPS)
+            .map(|i| {
+                let brightness = i as f64 / (HUD_STEPS - 1) as f64;
+                return hsl_color(self.color, brightness, brightness);
+            })
+            .collect();
+        ui.begin_panel("placer", Anchor::Top, HUD_WIDTH);
+        ui.color_bar(&shades, self.brightness as f32);
+        ui.color_swatch(self.calc_hsl_color());
+        ui.end_panel("placer");
+    }
+}
 
-        // preview rectangle
-        let color = self.calc_hsl_color();
-        draw_rectangle(start_x as f32, 32.0, estimated_size, 16.0, color);
-
-        // draw selected color
-        let estimated_pos = start_x + ((self.brightness as f32 * 36.0) * COLOR_HUD_WIDTH);
-        draw_rectangle(
-            estimated_pos,
-            8.0,
-            COLOR_HUD_WIDTH,
-            16.0,
-            Color::new(1.0, 1.0, 1.0, 0.5),
-        );
+// the closest color the placer can pick with the wheel, alpha is kept
+pub fn snap_to_palette(color: Color) -> Color {
+    let hsl = HSL::from_rgb(&[
+        (color.r * 255.0) as u8,
+        (color.g * 255.0) as u8,
+        (color.b * 255.0) as u8,
+    ]);
+    let hue = ((hsl.h / HUE_STEP).round() * HUE_STEP) % 360.0;
+    let steps = ((hsl.l - DEFAULT_BRIGHTNESS) / BRIGHTNESS_STEP).round();
+    let brightness = clamp(DEFAULT_BRIGHTNESS + steps * BRIGHTNESS_STEP, 0.0, 1.0);
+    let rgb = HSL {
+        h: hue,
+        s: brightness,
+        l: brightness,
     }
+    .to_rgb();
+    let mut snapped = Color::from_rgba(rgb.0, rgb.1, rgb.2, 255);
+    snapped.a = color.a;
+    return snapped;
+}
+
+fn hsl_color(h: f64, s: f64, l: f64) -> Color {
+    let rgb = HSL { h, s, l }.to_rgb();
+    return Color::from_rgba(rgb.0, rgb.1, rgb.2, 255);
 }
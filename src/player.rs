@@ -1,7 +1,11 @@
+use crate::atlas::atlas;
 use crate::block::Block;
-use crate::include_texture2d;
+use crate::block::BlockType;
+use crate::particle::ParticleSystem;
 use crate::position::GridPos;
 use crate::position::SubGridPos;
+use crate::settings::key;
+use crate::settings::Action;
 use crate::utils::aabb;
 use crate::utils::clamp;
 use crate::BLOCK_SIZE;
@@ -27,45 +31,94 @@ const NEIGHBORS: [(i32, i32); 16] = [
     (3, 3),
 ];
 const GRAVITY: f32 = 0.012;
+const PLAYER_SPRITE: &str = "sprites/player";
+pub const MAX_HEALTH: f32 = 100.0;
+// health lost per frame while touching lava
+const LAVA_DAMAGE: f32 = 1.5;
+// upward push per frame while holding jump in a liquid
+const SWIM_FORCE: f32 = 0.01;
+// falling faster than this kicks up dust on landing
+const DUST_SPEED: f32 = 0.2;
+// falling faster than this splashes when entering a liquid
+const SPLASH_SPEED: f32 = 0.1;
+// vertical velocity kept each frame when flying without pressing up or down
+const FLY_BRAKE: f32 = 0.8;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum GameMode {
+    Survival,
+    // flies and can't get hurt
+    Creative,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 2] = [GameMode::Survival, GameMode::Creative];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Survival => "survival",
+            GameMode::Creative => "creative",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<GameMode> {
+        return GameMode::ALL.into_iter().find(|mode| mode.name() == name);
+    }
+}
+
+// what the last collision check looked at, for the debug overlay
+#[derive(Default)]
+pub struct CollisionInfo {
+    // the NEIGHBORS cells around the new position and whether they were solid
+    pub tested: Vec<(GridPos, bool)>,
+    // the cells that stopped movement on each axis
+    pub x_blocker: Option<GridPos>,
+    pub y_blocker: Option<GridPos>,
+}
 
 pub struct Player {
     pub pos: SubGridPos,
     vel_x: f32,
     vel_y: f32,
     grounded: bool,
-    sprite: Option<Texture2D>,
     flip: bool,
+    pub health: f32,
+    pub mode: GameMode,
+    // the liquid we were in last frame, to notice jumping in
+    last_liquid: Option<BlockType>,
+    pub collision: CollisionInfo,
 }
 
 impl Player {
     pub fn new(pos: SubGridPos) -> Self {
-        let mut player = Player {
+        let player = Player {
             pos,
             vel_x: 0.0,
             vel_y: 0.0,
             grounded: false,
-            sprite: None,
             flip: false,
+            health: MAX_HEALTH,
+            mode: GameMode::Survival,
+            last_liquid: None,
+            collision: CollisionInfo::default(),
         };
-        player.sprite = Some(include_texture2d!("./assets/player.png"));
         return player;
     }
 
-    pub fn render(&self) {
-        if let Some(sprite) = &self.sprite {
-            let sprite_w = sprite.width();
-            let sprite_h = sprite.height();
-            draw_texture_ex(
-                sprite,
-                (self.pos.x * BLOCK_SIZE) - (sprite_w / 2.0) + (BLOCK_SIZE * (3.0 / 2.0)),
-                ((self.pos.y) * BLOCK_SIZE) - sprite_h + (BLOCK_SIZE * 3.0),
-                WHITE,
-                DrawTextureParams {
-                    flip_x: self.flip,
-                    ..Default::default()
-                },
-            )
-        }
+    pub fn render(&self, light: Color) {
+        let sprite = atlas().region(PLAYER_SPRITE);
+        let sprite_w = sprite.rect.w;
+        let sprite_h = sprite.rect.h;
+        atlas().draw(
+            PLAYER_SPRITE,
+            (self.pos.x * BLOCK_SIZE) - (sprite_w / 2.0) + (BLOCK_SIZE * (3.0 / 2.0)),
+            ((self.pos.y) * BLOCK_SIZE) - sprite_h + (BLOCK_SIZE * 3.0),
+            light,
+            DrawTextureParams {
+                flip_x: self.flip,
+                ..Default::default()
+            },
+        );
         /*draw_rectangle(
             self.pos.x * BLOCK_SIZE,
             self.pos.y * BLOCK_SIZE,
@@ -75,18 +128,94 @@ impl Player {
         );*/
     }
 
-    pub fn update(&mut self, blocks: &HashMap<GridPos, Block>) {
+    pub fn respawn(&mut self, pos: SubGridPos) {
+        self.pos = pos;
+        self.vel_x = 0.0;
+        self.vel_y = 0.0;
+        self.health = MAX_HEALTH;
+    }
+
+    // moves without healing, unlike respawn
+    pub fn teleport(&mut self, pos: SubGridPos) {
+        self.pos = pos;
+        self.vel_x = 0.0;
+        self.vel_y = 0.0;
+    }
+
+    pub fn is_dead(&self) -> bool {
+        return self.health <= 0.0;
+    }
+
+    // the liquid overlapping the player's hitbox, lava wins over water
+    fn submerged_in(&self, blocks: &HashMap<GridPos, Block>) -> Option<BlockType> {
+        let mut liquid = None;
+        for y in self.pos.y.floor() as i32..(self.pos.y + 3.0).ceil() as i32 {
+            for x in self.pos.x.floor() as i32..(self.pos.x + 3.0).ceil() as i32 {
+                let Some(block) = blocks.get(&GridPos::new(x, y, false)) else {
+                    continue;
+                };
+                // only count the filled part of the cell
+                let surface = y as f32 + 1.0 - block.level;
+                if !block.block_type.is_liquid() || surface > self.pos.y + 3.0 {
+                    continue;
+                }
+                if liquid != Some(BlockType::Lava) {
+                    liquid = Some(block.block_type);
+                }
+            }
+        }
+        return liquid;
+    }
+
+    // `wind` is pushed into the horizontal velocity every frame
+    pub fn update(
+        &mut self,
+        blocks: &HashMap<GridPos, Block>,
+        wind: f32,
+        particles: &mut ParticleSystem,
+    ) {
+        let liquid = self.submerged_in(blocks);
+        if let Some(kind) = liquid {
+            if self.last_liquid.is_none() && self.vel_y > SPLASH_SPEED {
+                let color = if kind == BlockType::Lava {
+                    Color::new(1.0, 0.45, 0.1, 1.0)
+                } else {
+                    Color::new(0.45, 0.6, 0.85, 0.8)
+                };
+                particles.emit_splash(self.pos.x + 1.5, self.pos.y + 3.0, color, 16);
+            }
+        }
+        self.last_liquid = liquid;
+        let speed = if liquid.is_some() { 0.15 } else { 0.3 };
+
         // INPUT
-        if is_key_down(KeyCode::Left) || is_key_down(KeyCode::A) {
+        if is_key_down(KeyCode::Left) || is_key_down(key(Action::MoveLeft)) {
             self.flip = true;
-            self.vel_x = -0.3;
+            self.vel_x = -speed;
         }
-        if is_key_down(KeyCode::Right) || is_key_down(KeyCode::D) {
+        if is_key_down(KeyCode::Right) || is_key_down(key(Action::MoveRight)) {
             self.flip = false;
-            self.vel_x = 0.3;
+            self.vel_x = speed;
         }
-        if is_key_down(KeyCode::Space) && self.grounded {
-            self.vel_y = -0.35;
+        let flying = self.mode == GameMode::Creative;
+        if flying {
+            if is_key_down(key(Action::Jump)) || is_key_down(key(Action::FlyUp)) {
+                self.vel_y = -speed;
+            } else if is_key_down(key(Action::FlyDown)) {
+                self.vel_y = speed;
+            } else {
+                self.vel_y *= FLY_BRAKE;
+            }
+        } else if is_key_down(key(Action::Jump)) {
+            if liquid.is_some() {
+                self.vel_y -= SWIM_FORCE;
+            } else if self.grounded {
+                self.vel_y = -0.35;
+            }
+        }
+
+        if liquid == Some(BlockType::Lava) && self.mode == GameMode::Survival {
+            self.health -= LAVA_DAMAGE;
         }
 
         // new position we'll try to move to
@@ -109,10 +238,17 @@ impl Player {
 
         // slow existing velocity over time
         self.vel_x *= 1.0 - (0.2);
-        self.vel_y = clamp(self.vel_y, -1.0, 1.0);
-
-        // apply gravity
-        self.vel_y += GRAVITY;
+        self.vel_x += wind;
+        if flying {
+            // no gravity while flying
+        } else if liquid.is_some() {
+            // liquids hold you up and slow you down
+            self.vel_y = clamp(self.vel_y, -0.15, 0.15);
+            self.vel_y += GRAVITY * 0.25;
+        } else {
+            self.vel_y = clamp(self.vel_y, -1.0, 1.0);
+            self.vel_y += GRAVITY;
+        }
 
         // calculate new position
         new_x += self.vel_x;
@@ -121,12 +257,18 @@ impl Player {
         // calculate fake grid position for the new pos
         let new_grid_x = new_x.round() as i32;
         let new_grid_y = new_y.round() as i32;
+        self.collision.tested.clear();
 
         for (x, y) in NEIGHBORS {
             let neighbor_grid_x = new_grid_x + x;
             let neighbor_grid_y = new_grid_y + y;
 
-            if let Some(_) = blocks.get(&GridPos::new(neighbor_grid_x, neighbor_grid_y, false)) {
+            let neighbor = blocks.get(&GridPos::new(neighbor_grid_x, neighbor_grid_y, false));
+            let solid = neighbor.is_some_and(|block| block.block_type.is_solid());
+            self.collision
+                .tested
+                .push((GridPos::new(neighbor_grid_x, neighbor_grid_y, false), solid));
+            if solid {
                 if !blocked_x {
                     blocked_x = aabb(
                         new_x,
@@ -156,6 +298,9 @@ impl Player {
             }
         }
 
+        self.collision.x_blocker = if blocked_x { Some(x_blocker) } else { None };
+        self.collision.y_blocker = if blocked_y { Some(y_blocker) } else { None };
+
         if blocked_x {
             self.vel_x = 0.0;
             // snap to left/right of blocking block
@@ -170,6 +315,7 @@ impl Player {
         }
 
         if blocked_y {
+            let landing_speed = self.vel_y;
             self.vel_y = 0.0;
             // blocker debug
             // snap to top/bottom of blocking block
@@ -179,7 +325,11 @@ impl Player {
                 self.pos.y = y_blocker.y as f32 + 1.0; // bottom
             }
             // grounded check
+            let was_grounded = self.grounded;
             self.grounded = y_blocker.y as f32 > self.pos.y;
+            if self.grounded && !was_grounded && landing_speed > DUST_SPEED {
+                particles.emit_dust(self.pos.x, self.pos.y + 3.0, 3.0, landing_speed);
+            }
         } else {
             // apply position
             self.pos.y = new_y;
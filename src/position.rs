@@ -7,7 +7,7 @@ pub struct GridPos {
 }
 impl GridPos {
     pub fn new(x: i32, y: i32, bg: bool) -> Self {
-        GridPos { x: x, y: y, bg: bg }
+        GridPos { x, y, bg }
     }
 }
 impl Add for GridPos {
@@ -0,0 +1,361 @@
+use crate::utils::draw_text_ex_shadow;
+use lazy_static::lazy_static;
+use macroquad::prelude::*;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::time::Instant;
+
+// frames kept for the graph
+const HISTORY: usize = 240;
+// a recording stops growing after this, about a minute at 60fps
+const MAX_RECORDED: usize = 3600;
+const GRAPH_WIDTH: f32 = 240.0;
+const GRAPH_HEIGHT: f32 = 80.0;
+// frame time at the top of the graph, in milliseconds
+const GRAPH_MS: f64 = 33.3;
+const TARGET_MS: f64 = 1000.0 / 60.0;
+const SECTION_COLORS: [Color; 8] = [
+    Color::new(0.35, 0.7, 1.0, 1.0),
+    Color::new(1.0, 0.6, 0.2, 1.0),
+    Color::new(0.4, 0.9, 0.4, 1.0),
+    Color::new(0.9, 0.4, 0.9, 1.0),
+    Color::new(1.0, 0.9, 0.3, 1.0),
+    Color::new(0.3, 0.9, 0.9, 1.0),
+    Color::new(1.0, 0.4, 0.4, 1.0),
+    Color::new(0.7, 0.7, 1.0, 1.0),
+];
+
+lazy_static! {
+    static ref PROFILER: Mutex<Profiler> = Mutex::new(Profiler::new());
+}
+
+fn profiler() -> MutexGuard<'static, Profiler> {
+    return PROFILER.lock().unwrap();
+}
+
+// one timed scope, times are in seconds since the profiler started
+#[derive(Clone)]
+struct Span {
+    name: &'static str,
+    start: f64,
+    duration: f64,
+    // how many scopes it was inside of
+    depth: usize,
+}
+
+#[derive(Clone, Default)]
+struct FrameProfile {
+    start: f64,
+    duration: f64,
+    spans: Vec<Span>,
+    counts: Vec<(&'static str, u64)>,
+}
+
+impl FrameProfile {
+    // time spent in top level scopes with this name, in seconds
+    fn section(&self, name: &str) -> f64 {
+        return self
+            .spans
+            .iter()
+            .filter(|span| span.depth == 0 && span.name == name)
+            .fold(0.0, |total, span| total + span.duration);
+    }
+
+    fn count(&self, name: &str) -> u64 {
+        return self
+            .counts
+            .iter()
+            .find(|(other, _)| *other == name)
+            .map_or(0, |(_, amount)| *amount);
+    }
+}
+
+// scoped timings and counters for the last few frames, shared by the whole game
+// so any code can time itself without a profiler being passed around
+struct Profiler {
+    epoch: Instant,
+    current: FrameProfile,
+    history: VecDeque<FrameProfile>,
+    recording: Option<Vec<FrameProfile>>,
+    // every section and count name in the order they first showed up
+    sections: Vec<&'static str>,
+    counters: Vec<&'static str>,
+    depth: usize,
+}
+
+impl Profiler {
+    fn new() -> Self {
+        Profiler {
+            epoch: Instant::now(),
+            current: FrameProfile::default(),
+            history: VecDeque::new(),
+            recording: None,
+            sections: Vec::new(),
+            counters: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    fn seconds(&self, instant: Instant) -> f64 {
+        return instant.duration_since(self.epoch).as_secs_f64();
+    }
+
+    fn color(&self, name: &str) -> Color {
+        let index = self.sections.iter().position(|other| *other == name);
+        return SECTION_COLORS[index.unwrap_or(0) % SECTION_COLORS.len()];
+    }
+}
+
+// times everything until it's dropped, so `let _scope = scope("name");` times the
+// rest of the block. scopes inside other scopes show up in traces but not the graph
+pub struct Scope {
+    name: &'static str,
+    start: Instant,
+}
+
+pub fn scope(name: &'static str) -> Scope {
+    profiler().depth += 1;
+    return Scope {
+        name,
+        start: Instant::now(),
+    };
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed();
+        let mut profiler = profiler();
+        profiler.depth -= 1;
+        let start = profiler.seconds(self.start);
+        let depth = profiler.depth;
+        if depth == 0 && !profiler.sections.contains(&self.name) {
+            profiler.sections.push(self.name);
+        }
+        profiler.current.spans.push(Span {
+            name: self.name,
+            start,
+            duration: duration.as_secs_f64(),
+            depth,
+        });
+    }
+}
+
+// adds to a counter for this frame
+pub fn count(name: &'static str, amount: u64) {
+    let mut profiler = profiler();
+    if !profiler.counters.contains(&name) {
+        profiler.counters.push(name);
+    }
+    match profiler
+        .current
+        .counts
+        .iter_mut()
+        .find(|(other, _)| *other == name)
+    {
+        Some((_, total)) => *total += amount,
+        None => profiler.current.counts.push((name, amount)),
+    }
+}
+
+// closes the current frame and starts the next, once per main loop iteration
+pub fn end_frame() {
+    let mut profiler = profiler();
+    let now = profiler.seconds(Instant::now());
+    let mut frame = std::mem::take(&mut profiler.current);
+    frame.duration = now - frame.start;
+    profiler.current.start = now;
+
+    // the first frame has no start, it's just the time the game took to load
+    if frame.start == 0.0 {
+        return;
+    }
+    if let Some(recording) = profiler.recording.as_mut() {
+        if recording.len() < MAX_RECORDED {
+            recording.push(frame.clone());
+        }
+    }
+    profiler.history.push_back(frame);
+    if profiler.history.len() > HISTORY {
+        profiler.history.pop_front();
+    }
+}
+
+pub fn start_recording() {
+    profiler().recording = Some(Vec::new());
+}
+
+pub fn is_recording() -> bool {
+    return profiler().recording.is_some();
+}
+
+// stops recording and writes what was recorded, a .csv gets one row per frame
+// and anything else a Chrome trace for chrome://tracing or Perfetto.
+// returns how many frames were written
+pub fn save_recording(path: &str) -> Result<usize, String> {
+    let mut profiler = profiler();
+    let frames = profiler.recording.take().ok_or("not recording")?;
+    let is_csv = Path::new(path)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    let text = if is_csv {
+        csv(&frames, &profiler.sections, &profiler.counters)
+    } else {
+        chrome_trace(&frames)
+    };
+    fs::write(path, text).map_err(|err| format!("couldn't write {}: {}", path, err))?;
+    return Ok(frames.len());
+}
+
+fn csv(frames: &[FrameProfile], sections: &[&str], counters: &[&str]) -> String {
+    let mut header = vec!["frame".to_owned(), "frame_ms".to_owned()];
+    header.extend(
+        sections
+            .iter()
+            .map(|name| format!("{}_ms", name.replace(' ', "_"))),
+    );
+    header.extend(counters.iter().map(|name| name.replace(' ', "_")));
+    let mut lines = vec![header.join(",")];
+    for (index, frame) in frames.iter().enumerate() {
+        let mut row = vec![index.to_string(), format!("{:.3}", frame.duration * 1000.0)];
+        row.extend(
+            sections
+                .iter()
+                .map(|name| format!("{:.3}", frame.section(name) * 1000.0)),
+        );
+        row.extend(counters.iter().map(|name| frame.count(name).to_string()));
+        lines.push(row.join(","));
+    }
+    return lines.join("\n") + "\n";
+}
+
+// complete events for every scope and frame, and counter events for the counts
+fn chrome_trace(frames: &[FrameProfile]) -> String {
+    let micros = |seconds: f64| -> f64 {
+        return (seconds * 1_000_000.0).round();
+    };
+    let mut events: Vec<serde_json::Value> = Vec::new();
+    for frame in frames {
+        events.push(serde_json::json!({
+            "name": "frame",
+            "ph": "X",
+            "pid": 1,
+            "tid": 1,
+            "ts": micros(frame.start),
+            "dur": micros(frame.duration),
+        }));
+        for span in &frame.spans {
+            events.push(serde_json::json!({
+                "name": span.name,
+                "ph": "X",
+                "pid": 1,
+                "tid": 1,
+                "ts": micros(span.start),
+                "dur": micros(span.duration),
+            }));
+        }
+        for (name, amount) in &frame.counts {
+            events.push(serde_json::json!({
+                "name": name,
+                "ph": "C",
+                "pid": 1,
+                "ts": micros(frame.start),
+                "args": { "value": amount },
+            }));
+        }
+    }
+    return serde_json::json!({ "traceEvents": events }).to_string();
+}
+
+// the rolling frame time graph with a bar per frame split into the top level
+// sections, and their averages and the last frame's counts next to it
+pub fn render_graph(font: &Font, x: f32, y: f32) {
+    let profiler = profiler();
+    let text = |message: &str, line: usize, color: Color| {
+        draw_text_ex_shadow(
+            message,
+            x + GRAPH_WIDTH + 10.0,
+            y + 14.0 + 16.0 * line as f32,
+            TextParams {
+                font: Some(font),
+                font_size: 14,
+                color,
+                ..Default::default()
+            },
+        );
+    };
+
+    draw_rectangle(
+        x,
+        y,
+        GRAPH_WIDTH,
+        GRAPH_HEIGHT,
+        Color::new(0.0, 0.0, 0.0, 0.5),
+    );
+    let bar_width = GRAPH_WIDTH / HISTORY as f32;
+    let height = |seconds: f64| -> f32 {
+        return (seconds * 1000.0 / GRAPH_MS).min(1.0) as f32 * GRAPH_HEIGHT;
+    };
+    for (index, frame) in profiler.history.iter().enumerate() {
+        let left = x + index as f32 * bar_width;
+        let bottom = y + GRAPH_HEIGHT;
+        let total = height(frame.duration);
+        draw_rectangle(left, bottom - total, bar_width, total, GRAY);
+        let mut stacked = 0.0;
+        for name in &profiler.sections {
+            let section = height(frame.section(name)).min(total - stacked);
+            if section <= 0.0 {
+                continue;
+            }
+            stacked += section;
+            draw_rectangle(
+                left,
+                bottom - stacked,
+                bar_width,
+                section,
+                profiler.color(name),
+            );
+        }
+    }
+    let target_y = y + GRAPH_HEIGHT - height(TARGET_MS / 1000.0);
+    draw_line(x, target_y, x + GRAPH_WIDTH, target_y, 1.0, WHITE);
+    draw_rectangle_lines(x, y, GRAPH_WIDTH, GRAPH_HEIGHT, 1.0, WHITE);
+
+    let frames = profiler.history.len().max(1) as f64;
+    let average = |seconds: f64| -> f64 {
+        return seconds / frames * 1000.0;
+    };
+    let frame_time: f64 = profiler.history.iter().map(|frame| frame.duration).sum();
+    let mut line = 0;
+    let recording = if profiler.recording.is_some() {
+        " (recording)"
+    } else {
+        ""
+    };
+    text(
+        &format!("frame: {:.2} ms{}", average(frame_time), recording),
+        line,
+        WHITE,
+    );
+    for name in &profiler.sections {
+        line += 1;
+        let total: f64 = profiler
+            .history
+            .iter()
+            .map(|frame| frame.section(name))
+            .sum();
+        text(
+            &format!("{}: {:.2} ms", name, average(total)),
+            line,
+            profiler.color(name),
+        );
+    }
+    if let Some(last) = profiler.history.back() {
+        for name in &profiler.counters {
+            line += 1;
+            text(&format!("{}: {}", name, last.count(name)), line, WHITE);
+        }
+    }
+}
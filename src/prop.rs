@@ -0,0 +1,71 @@
+use crate::entity::Entity;
+use crate::particle::ParticleSystem;
+use crate::position::SubGridPos;
+use crate::BLOCK_SIZE;
+use macroquad::prelude::*;
+
+// a placeholder entity for objects from map files, drawn as a box until
+// object kinds mean something to the game
+pub struct Prop {
+    pub id: u64,
+    pub pos: SubGridPos,
+    // in blocks
+    pub width: f32,
+    pub height: f32,
+    pub color: Color,
+    // the object's name, class and custom properties from the map
+    pub name: String,
+    pub class: String,
+    pub properties: Vec<(String, String)>,
+}
+
+impl Entity for Prop {
+    fn spawn(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    fn update(&mut self, _: &mut ParticleSystem) {}
+
+    fn render(&self) {
+        let x = self.pos.x * BLOCK_SIZE;
+        let y = self.pos.y * BLOCK_SIZE;
+        let width = self.width * BLOCK_SIZE;
+        let height = self.height * BLOCK_SIZE;
+        draw_rectangle(x, y, width, height, self.color);
+        draw_rectangle_lines(x, y, width, height, 1.0, WHITE);
+    }
+
+    fn get_id(&self) -> u64 {
+        return self.id;
+    }
+
+    fn get_pos(&self) -> SubGridPos {
+        return self.pos;
+    }
+
+    // props never move
+    fn get_vel(&self) -> (f32, f32) {
+        return (0.0, 0.0);
+    }
+
+    fn hitbox(&self) -> Rect {
+        return Rect::new(self.pos.x, self.pos.y, self.width, self.height);
+    }
+
+    fn kind(&self) -> &str {
+        return "prop";
+    }
+
+    fn inspect(&self) -> Vec<(String, String)> {
+        let mut state = vec![
+            ("name".to_owned(), self.name.clone()),
+            ("class".to_owned(), self.class.clone()),
+            (
+                "size".to_owned(),
+                format!("{:.2} x {:.2}", self.width, self.height),
+            ),
+        ];
+        state.extend(self.properties.iter().cloned());
+        return state;
+    }
+}
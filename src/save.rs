@@ -0,0 +1,235 @@
+use crate::block::Block;
+use crate::block::BlockOverlay;
+use crate::block::BlockType;
+use crate::position::GridPos;
+use crate::position::SubGridPos;
+use crate::terrain::CHUNK_WIDTH;
+use crate::utils::color_to_hex;
+use crate::utils::hex_color;
+use crate::utils::is_hex_color;
+use crate::world::inside_world;
+use crate::world::new_world;
+use crate::world::World;
+use std::collections::HashMap;
+use std::fs;
+
+// world files are plain text with one entry per line:
+//
+//   vexalab-world 1
+//   seed 1234
+//   time 0.3125
+//   player 12.5 20
+//   chunks -2 -1 0 1 2
+//   block 10 24 fg solid #1f3029 1 top 1
+//
+// a block line is `x y layer type color alpha overlay level`. chunks lists every
+// chunk that was generated so saved changes aren't overwritten by fresh terrain
+pub const WORLD_FILE_HEADER: &str = "vexalab-world";
+pub const WORLD_FILE_VERSION: u32 = 1;
+
+// everything a world file holds, without needing a window to exist
+pub struct WorldFile {
+    pub seed: u32,
+    pub time: f32,
+    pub player: SubGridPos,
+    pub chunks: Vec<i32>,
+    pub blocks: HashMap<GridPos, Block>,
+}
+
+impl WorldFile {
+    pub fn from_world(world: &World) -> Self {
+        let mut chunks: Vec<i32> = world.generated_chunks.iter().copied().collect();
+        chunks.sort();
+        WorldFile {
+            seed: world.terrain.seed,
+            time: world.clock.time,
+            player: world.player.pos,
+            chunks,
+            blocks: world.blocks.clone(),
+        }
+    }
+
+    pub fn into_world(self) -> World {
+        let mut world = new_world(self.seed);
+        world.clock.time = self.time;
+        world.player.respawn(self.player);
+        world.camera.snap_to(self.player);
+        for chunk in self.chunks {
+            world.generated_chunks.insert(chunk);
+            let start_x = chunk * CHUNK_WIDTH;
+            world
+                .light
+                .mark_dirty_range(start_x, start_x + CHUNK_WIDTH - 1);
+        }
+        // files from other tools can have blocks the world has no room for
+        world.blocks = self
+            .blocks
+            .into_iter()
+            .filter(|(pos, _)| inside_world(*pos))
+            .collect();
+        return world;
+    }
+
+    pub fn read(path: &str) -> Result<WorldFile, String> {
+        let source =
+            fs::read_to_string(path).map_err(|err| format!("couldn't read {}: {}", path, err))?;
+        return WorldFile::parse(&source).map_err(|err| format!("{}: {}", path, err));
+    }
+
+    pub fn write(&self, path: &str) -> Result<(), String> {
+        return fs::write(path, self.to_text())
+            .map_err(|err| format!("couldn't write {}: {}", path, err));
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        lines.push(format!("{} {}", WORLD_FILE_HEADER, WORLD_FILE_VERSION));
+        lines.push(format!("seed {}", self.seed));
+        lines.push(format!("time {}", self.time));
+        lines.push(format!("player {} {}", self.player.x, self.player.y));
+        let chunks: Vec<String> = self.chunks.iter().map(|chunk| chunk.to_string()).collect();
+        lines.push(format!("chunks {}", chunks.join(" ")));
+
+        // sorted so saving the same world twice gives the same file
+        let mut blocks: Vec<(&GridPos, &Block)> = self.blocks.iter().collect();
+        blocks.sort_by_key(|(pos, _)| (pos.bg, pos.y, pos.x));
+        for (pos, block) in blocks {
+            lines.push(format!(
+                "block {} {} {} {} {} {} {} {}",
+                pos.x,
+                pos.y,
+                if pos.bg { "bg" } else { "fg" },
+                block.block_type.name(),
+                color_to_hex(block.color),
+                block.color.a,
+                block.overlay.name(),
+                block.level
+            ));
+        }
+
+        return lines.join("\n") + "\n";
+    }
+
+    pub fn parse(source: &str) -> Result<WorldFile, String> {
+        let mut lines = source.lines().enumerate();
+
+        let (_, header) = lines.next().ok_or("empty world file")?;
+        let version = header
+            .strip_prefix(WORLD_FILE_HEADER)
+            .and_then(|version| version.trim().parse::<u32>().ok())
+            .ok_or("not a vexalab world file")?;
+        if version != WORLD_FILE_VERSION {
+            return Err(format!("unsupported world file version {}", version));
+        }
+
+        let mut file = WorldFile {
+            seed: 0,
+            time: 0.0,
+            player: SubGridPos { x: 0.0, y: 0.0 },
+            chunks: Vec::new(),
+            blocks: HashMap::new(),
+        };
+        let mut has_seed = false;
+        for (index, line) in lines {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let key = parts.next().unwrap();
+            let values: Vec<&str> = parts.collect();
+            let error = |message: String| format!("line {}: {}", index + 1, message);
+
+            match key {
+                "seed" => {
+                    file.seed = parse(&values, 0).map_err(error)?;
+                    has_seed = true;
+                }
+                "time" => file.time = parse_unit(&values, 0).map_err(error)?,
+                "player" => {
+                    file.player = SubGridPos {
+                        x: parse_finite(&values, 0).map_err(error)?,
+                        y: parse_finite(&values, 1).map_err(error)?,
+                    }
+                }
+                "chunks" => {
+                    for index in 0..values.len() {
+                        file.chunks.push(parse(&values, index).map_err(error)?);
+                    }
+                }
+                "block" => {
+                    let (pos, block) = parse_block(&values).map_err(error)?;
+                    file.blocks.insert(pos, block);
+                }
+                _ => return Err(error(format!("unknown entry `{}`", key))),
+            }
+        }
+
+        if !has_seed {
+            return Err("world file has no seed".to_owned());
+        }
+        return Ok(file);
+    }
+}
+
+pub fn save_world(world: &World, path: &str) -> Result<(), String> {
+    return WorldFile::from_world(world).write(path);
+}
+
+pub fn load_world(path: &str) -> Result<World, String> {
+    return Ok(WorldFile::read(path)?.into_world());
+}
+
+fn parse<T: std::str::FromStr>(values: &[&str], index: usize) -> Result<T, String> {
+    let value = values
+        .get(index)
+        .ok_or(format!("missing value {}", index + 1))?;
+    return value
+        .parse()
+        .map_err(|_| format!("invalid value `{}`", value));
+}
+
+fn parse_finite(values: &[&str], index: usize) -> Result<f32, String> {
+    let value: f32 = parse(values, index)?;
+    if !value.is_finite() {
+        return Err(format!("invalid value `{}`", values[index]));
+    }
+    return Ok(value);
+}
+
+// times of day, alphas and levels, which all go from 0 to 1
+fn parse_unit(values: &[&str], index: usize) -> Result<f32, String> {
+    let value: f32 = parse(values, index)?;
+    if !(0.0..=1.0).contains(&value) {
+        return Err(format!("value `{}` is outside 0 to 1", values[index]));
+    }
+    return Ok(value);
+}
+
+fn parse_block(values: &[&str]) -> Result<(GridPos, Block), String> {
+    if values.len() != 8 {
+        return Err(format!("expected 8 block values, got {}", values.len()));
+    }
+    let bg = match values[2] {
+        "fg" => false,
+        "bg" => true,
+        layer => return Err(format!("unknown layer `{}`", layer)),
+    };
+    let block_type =
+        BlockType::from_name(values[3]).ok_or(format!("unknown block type `{}`", values[3]))?;
+    let color = values[4];
+    if !is_hex_color(color) {
+        return Err(format!("invalid color `{}`", color));
+    }
+    let overlay =
+        BlockOverlay::from_name(values[6]).ok_or(format!("unknown overlay `{}`", values[6]))?;
+
+    let mut block = Block::new(
+        block_type,
+        hex_color(color, parse_unit(values, 5)?),
+        overlay,
+    );
+    block.level = parse_unit(values, 7)?;
+    let pos = GridPos::new(parse(values, 0)?, parse(values, 1)?, bg);
+    return Ok((pos, block));
+}
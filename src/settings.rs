@@ -0,0 +1,489 @@
+use crate::camera::CameraController;
+use crate::camera::CameraSettings;
+use lazy_static::lazy_static;
+use macroquad::prelude::*;
+use std::fs;
+use std::sync::RwLock;
+
+// settings files are plain text with one setting per line, anything left out
+// keeps its default:
+//
+//   vexalab-settings 1
+//   window 900 600
+//   fullscreen off
+//   vsync on
+//   ui_scale 1
+//   zoom 6
+//   camera_smoothing 0.5
+//   debug_text on
+//   bind jump Space
+//
+// values that don't parse or are out of range are reported and the default kept
+pub const SETTINGS_PATH: &str = "settings.txt";
+const SETTINGS_HEADER: &str = "vexalab-settings";
+const SETTINGS_VERSION: u32 = 1;
+
+pub const MIN_WINDOW: (u32, u32) = (320, 240);
+pub const MAX_WINDOW: (u32, u32) = (7680, 4320);
+pub const MIN_UI_SCALE: f32 = 0.75;
+pub const MAX_UI_SCALE: f32 = 2.0;
+// the default follow speeds, which a smoothing of 0.5 gives
+const FOLLOW_X: f32 = 0.1;
+const FOLLOW_Y_RATIO: f32 = 0.2;
+
+// everything that can be bound to a key
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Jump,
+    FlyUp,
+    FlyDown,
+    NextBlock,
+    Inspect,
+    Map,
+    Console,
+    FreeCamera,
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    QuickSave,
+    QuickLoad,
+    ImportMap,
+    ExportMap,
+    ImportImage,
+    ExportWorld,
+    Screenshot,
+    DebugCollision,
+    DebugCulling,
+    DebugChunks,
+    DebugEntities,
+    FrameStepping,
+    StepFrame,
+    Profiler,
+    ColorWheel,
+    BrightnessWheel,
+}
+
+impl Action {
+    pub const ALL: [Action; 30] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::FlyUp,
+        Action::FlyDown,
+        Action::NextBlock,
+        Action::Inspect,
+        Action::Map,
+        Action::Console,
+        Action::FreeCamera,
+        Action::PanLeft,
+        Action::PanRight,
+        Action::PanUp,
+        Action::PanDown,
+        Action::QuickSave,
+        Action::QuickLoad,
+        Action::ImportMap,
+        Action::ExportMap,
+        Action::ImportImage,
+        Action::ExportWorld,
+        Action::Screenshot,
+        Action::DebugCollision,
+        Action::DebugCulling,
+        Action::DebugChunks,
+        Action::DebugEntities,
+        Action::FrameStepping,
+        Action::StepFrame,
+        Action::Profiler,
+        Action::ColorWheel,
+        Action::BrightnessWheel,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "left",
+            Action::MoveRight => "right",
+            Action::Jump => "jump",
+            Action::FlyUp => "fly_up",
+            Action::FlyDown => "fly_down",
+            Action::NextBlock => "next_block",
+            Action::Inspect => "inspect",
+            Action::Map => "map",
+            Action::Console => "console",
+            Action::FreeCamera => "free_camera",
+            Action::PanLeft => "pan_left",
+            Action::PanRight => "pan_right",
+            Action::PanUp => "pan_up",
+            Action::PanDown => "pan_down",
+            Action::QuickSave => "quicksave",
+            Action::QuickLoad => "quickload",
+            Action::ImportMap => "import_map",
+            Action::ExportMap => "export_map",
+            Action::ImportImage => "import_image",
+            Action::ExportWorld => "export_world",
+            Action::Screenshot => "screenshot",
+            Action::DebugCollision => "debug_collision",
+            Action::DebugCulling => "debug_culling",
+            Action::DebugChunks => "debug_chunks",
+            Action::DebugEntities => "debug_entities",
+            Action::FrameStepping => "frame_stepping",
+            Action::StepFrame => "step_frame",
+            Action::Profiler => "profiler",
+            Action::ColorWheel => "color_wheel",
+            Action::BrightnessWheel => "brightness_wheel",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        return Action::ALL.into_iter().find(|action| action.name() == name);
+    }
+
+    fn default_key(&self) -> KeyCode {
+        match self {
+            Action::MoveLeft => KeyCode::A,
+            Action::MoveRight => KeyCode::D,
+            Action::Jump => KeyCode::Space,
+            Action::FlyUp => KeyCode::W,
+            Action::FlyDown => KeyCode::S,
+            Action::NextBlock => KeyCode::Q,
+            Action::Inspect => KeyCode::LeftShift,
+            Action::Map => KeyCode::M,
+            Action::Console => KeyCode::GraveAccent,
+            Action::FreeCamera => KeyCode::C,
+            Action::PanLeft => KeyCode::J,
+            Action::PanRight => KeyCode::L,
+            Action::PanUp => KeyCode::I,
+            Action::PanDown => KeyCode::K,
+            Action::QuickSave => KeyCode::F5,
+            Action::QuickLoad => KeyCode::F9,
+            Action::ImportMap => KeyCode::F7,
+            Action::ExportMap => KeyCode::F8,
+            Action::ImportImage => KeyCode::F10,
+            Action::ExportWorld => KeyCode::F11,
+            Action::Screenshot => KeyCode::F12,
+            Action::DebugCollision => KeyCode::F1,
+            Action::DebugCulling => KeyCode::F2,
+            Action::DebugChunks => KeyCode::F3,
+            Action::DebugEntities => KeyCode::F4,
+            Action::FrameStepping => KeyCode::F6,
+            Action::StepFrame => KeyCode::Period,
+            Action::Profiler => KeyCode::P,
+            Action::ColorWheel => KeyCode::LeftControl,
+            Action::BrightnessWheel => KeyCode::LeftAlt,
+        }
+    }
+}
+
+// keys that can be bound, by the name they're written with. escape always pauses
+// and the left and right arrows always move, so they can't be
+const BINDABLE_KEYS: [KeyCode; 69] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Space,
+    KeyCode::Tab,
+    KeyCode::Enter,
+    KeyCode::Backspace,
+    KeyCode::GraveAccent,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::LeftBracket,
+    KeyCode::RightBracket,
+    KeyCode::Semicolon,
+    KeyCode::Apostrophe,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::LeftShift,
+    KeyCode::RightShift,
+    KeyCode::LeftControl,
+    KeyCode::LeftAlt,
+    KeyCode::RightAlt,
+];
+
+pub fn key_name(key: KeyCode) -> String {
+    return format!("{:?}", key);
+}
+
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    return BINDABLE_KEYS
+        .into_iter()
+        .find(|key| key_name(*key).eq_ignore_ascii_case(name));
+}
+
+pub fn is_bindable(key: KeyCode) -> bool {
+    return BINDABLE_KEYS.contains(&key);
+}
+
+lazy_static! {
+    static ref BINDINGS: RwLock<[KeyCode; Action::ALL.len()]> =
+        RwLock::new(Action::ALL.map(|action| action.default_key()));
+}
+
+// the key an action is bound to right now, for input code anywhere in the game
+pub fn key(action: Action) -> KeyCode {
+    return BINDINGS.read().unwrap()[action as usize];
+}
+
+pub struct Settings {
+    pub window_width: u32,
+    pub window_height: u32,
+    pub fullscreen: bool,
+    // only takes effect on the next start
+    pub vsync: bool,
+    pub ui_scale: f32,
+    // what new and loaded worlds start zoomed to
+    pub zoom: f32,
+    // 0 snaps the camera to the player, 1 barely follows
+    pub camera_smoothing: f32,
+    pub debug_text: bool,
+    keys: [KeyCode; Action::ALL.len()],
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            window_width: 900,
+            window_height: 600,
+            fullscreen: false,
+            vsync: true,
+            ui_scale: 1.0,
+            zoom: 6.0,
+            camera_smoothing: 0.5,
+            debug_text: true,
+            keys: Action::ALL.map(|action| action.default_key()),
+        }
+    }
+}
+
+impl Settings {
+    // the defaults when there's no file yet. returns what was wrong with the file
+    pub fn load(path: &str) -> (Settings, Vec<String>) {
+        let Ok(source) = fs::read_to_string(path) else {
+            return (Settings::default(), Vec::new());
+        };
+        let (settings, problems) = Settings::parse(&source);
+        let problems = problems
+            .into_iter()
+            .map(|problem| format!("{}: {}", path, problem))
+            .collect();
+        return (settings, problems);
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        return fs::write(path, self.to_text())
+            .map_err(|err| format!("couldn't write {}: {}", path, err));
+    }
+
+    pub fn to_text(&self) -> String {
+        let on_off = |value: bool| if value { "on" } else { "off" };
+        let mut lines: Vec<String> = vec![
+            format!("{} {}", SETTINGS_HEADER, SETTINGS_VERSION),
+            format!("window {} {}", self.window_width, self.window_height),
+            format!("fullscreen {}", on_off(self.fullscreen)),
+            format!("vsync {}", on_off(self.vsync)),
+            format!("ui_scale {}", self.ui_scale),
+            format!("zoom {}", self.zoom),
+            format!("camera_smoothing {}", self.camera_smoothing),
+            format!("debug_text {}", on_off(self.debug_text)),
+        ];
+        for action in Action::ALL {
+            lines.push(format!(
+                "bind {} {}",
+                action.name(),
+                key_name(self.key(action))
+            ));
+        }
+        return lines.join("\n") + "\n";
+    }
+
+    // never fails, every line that can't be used is skipped and reported
+    pub fn parse(source: &str) -> (Settings, Vec<String>) {
+        let mut settings = Settings::default();
+        let mut problems: Vec<String> = Vec::new();
+        let mut lines = source.lines().enumerate();
+
+        let version = lines.next().and_then(|(_, header)| {
+            return header
+                .strip_prefix(SETTINGS_HEADER)
+                .and_then(|version| version.trim().parse::<u32>().ok());
+        });
+        match version {
+            Some(SETTINGS_VERSION) => {}
+            Some(version) => problems.push(format!("unsupported version {}", version)),
+            None => {
+                problems.push("not a vexalab settings file, using the defaults".to_owned());
+                return (settings, problems);
+            }
+        }
+
+        for (index, line) in lines {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let key = parts.next().unwrap();
+            let values: Vec<&str> = parts.collect();
+            if let Err(err) = settings.set(key, &values) {
+                problems.push(format!("line {}: {}", index + 1, err));
+            }
+        }
+        problems.extend(settings.conflicts());
+        return (settings, problems);
+    }
+
+    // one line of the file, anything out of range leaves the setting alone
+    fn set(&mut self, key: &str, values: &[&str]) -> Result<(), String> {
+        match key {
+            "window" => {
+                let width: u32 = parse(values, 0)?;
+                let height: u32 = parse(values, 1)?;
+                if !(MIN_WINDOW.0..=MAX_WINDOW.0).contains(&width)
+                    || !(MIN_WINDOW.1..=MAX_WINDOW.1).contains(&height)
+                {
+                    return Err(format!("window size {}x{} is out of range", width, height));
+                }
+                self.window_width = width;
+                self.window_height = height;
+            }
+            "fullscreen" => self.fullscreen = parse_switch(values)?,
+            "vsync" => self.vsync = parse_switch(values)?,
+            "debug_text" => self.debug_text = parse_switch(values)?,
+            "ui_scale" => {
+                self.ui_scale = parse_ranged(values, "ui_scale", MIN_UI_SCALE, MAX_UI_SCALE)?
+            }
+            "zoom" => {
+                let camera = CameraSettings::default();
+                self.zoom = parse_ranged(values, "zoom", camera.min_zoom, camera.max_zoom)?;
+            }
+            "camera_smoothing" => {
+                self.camera_smoothing = parse_ranged(values, "camera_smoothing", 0.0, 1.0)?
+            }
+            "bind" => {
+                let name = values.first().ok_or("missing action")?;
+                let action = Action::from_name(name).ok_or(format!("unknown action `{}`", name))?;
+                let key_name = values.get(1).ok_or("missing key")?;
+                let key = key_from_name(key_name).ok_or(format!("can't bind `{}`", key_name))?;
+                self.bind(action, key);
+            }
+            _ => return Err(format!("unknown setting `{}`", key)),
+        }
+        return Ok(());
+    }
+
+    // actions that share a key, which is allowed but usually a mistake
+    fn conflicts(&self) -> Vec<String> {
+        let mut conflicts: Vec<String> = Vec::new();
+        for (index, action) in Action::ALL.iter().enumerate() {
+            for other in &Action::ALL[index + 1..] {
+                if self.key(*action) == self.key(*other) {
+                    conflicts.push(format!(
+                        "{} and {} are both bound to {}",
+                        action.name(),
+                        other.name(),
+                        key_name(self.key(*action))
+                    ));
+                }
+            }
+        }
+        return conflicts;
+    }
+
+    pub fn key(&self, action: Action) -> KeyCode {
+        return self.keys[action as usize];
+    }
+
+    pub fn bind(&mut self, action: Action, key: KeyCode) {
+        self.keys[action as usize] = key;
+    }
+
+    // makes the bindings the ones `key` answers with
+    pub fn apply_bindings(&self) {
+        *BINDINGS.write().unwrap() = self.keys;
+    }
+
+    // zoom only applies when `zoom` is set, so a running game keeps its own
+    pub fn apply_camera(&self, camera: &mut CameraController, zoom: bool) {
+        let follow = FOLLOW_X.powf(self.camera_smoothing * 2.0);
+        camera.settings.follow_x = follow;
+        camera.settings.follow_y = follow * FOLLOW_Y_RATIO;
+        if zoom {
+            camera.zoom = self.zoom;
+        }
+    }
+}
+
+fn parse<T: std::str::FromStr>(values: &[&str], index: usize) -> Result<T, String> {
+    let value = values
+        .get(index)
+        .ok_or(format!("missing value {}", index + 1))?;
+    return value
+        .parse()
+        .map_err(|_| format!("invalid value `{}`", value));
+}
+
+fn parse_switch(values: &[&str]) -> Result<bool, String> {
+    match values.first().copied() {
+        Some("on") | Some("true") | Some("1") => return Ok(true),
+        Some("off") | Some("false") | Some("0") => return Ok(false),
+        Some(value) => return Err(format!("expected on or off, got `{}`", value)),
+        None => return Err("missing value 1".to_owned()),
+    }
+}
+
+fn parse_ranged(values: &[&str], name: &str, min: f32, max: f32) -> Result<f32, String> {
+    let value: f32 = parse(values, 0)?;
+    if !value.is_finite() || value < min || value > max {
+        return Err(format!("{} {} is outside {} to {}", name, value, min, max));
+    }
+    return Ok(value);
+}
@@ -0,0 +1,207 @@
+use crate::biome::Biome;
+use crate::block::Block;
+use crate::block::BlockOverlay;
+use crate::block::BlockType;
+use crate::utils::hex_color;
+use crate::utils::is_hex_color;
+use std::collections::HashMap;
+
+// where a structure is allowed to go
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Placement {
+    // bottom row rests on top of fairly flat ground
+    Ground,
+    // fully buried below the surface
+    Underground,
+    // both ends on ground with a dip in between (bridges)
+    Span,
+}
+
+#[derive(Clone, Copy)]
+pub enum StructureCell {
+    // leave whatever the terrain put there
+    Keep,
+    // remove the foreground block
+    Air,
+    // foreground block, or a background block that also clears the foreground
+    Block { block: Block, bg: bool },
+}
+
+pub struct Structure {
+    pub name: String,
+    pub placement: Placement,
+    // minimum distance in columns between two structures of this kind
+    pub spacing: i32,
+    // chance that a possible spot actually gets one
+    pub chance: f32,
+    pub biomes: Vec<Biome>,
+    pub width: i32,
+    pub height: i32,
+    // rows from top to bottom
+    pub cells: Vec<Vec<StructureCell>>,
+}
+
+impl Structure {
+    // template format, one `key: value` per line and the layout last:
+    //
+    //   name: hut
+    //   placement: ground | underground | span
+    //   spacing: 96
+    //   chance: 0.5
+    //   biomes: plains, forest
+    //   palette: R #6e2f25 top      (char, color, then optional `top`, `bg` or a block type)
+    //   layout:
+    //   ..RRR..
+    //
+    // in the layout a space keeps the terrain and `.` carves air
+    pub fn parse(source: &str) -> Result<Structure, String> {
+        let mut name = String::new();
+        let mut placement = Placement::Ground;
+        let mut spacing = 64;
+        let mut chance = 1.0;
+        let mut biomes: Vec<Biome> = Biome::ALL.to_vec();
+        let mut palette: HashMap<char, StructureCell> = HashMap::new();
+        let mut rows: Vec<&str> = Vec::new();
+        let mut in_layout = false;
+
+        for line in source.lines() {
+            if in_layout {
+                rows.push(line);
+                continue;
+            }
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .ok_or(format!("expected `key: value`, got `{}`", line))?;
+            let value = value.trim();
+            match key.trim() {
+                "name" => name = value.to_owned(),
+                "placement" => {
+                    placement = match value {
+                        "ground" => Placement::Ground,
+                        "underground" => Placement::Underground,
+                        "span" => Placement::Span,
+                        _ => return Err(format!("unknown placement `{}`", value)),
+                    }
+                }
+                "spacing" => {
+                    spacing = value
+                        .parse()
+                        .map_err(|_| format!("invalid spacing `{}`", value))?
+                }
+                "chance" => {
+                    chance = value
+                        .parse()
+                        .map_err(|_| format!("invalid chance `{}`", value))?
+                }
+                "biomes" => {
+                    biomes = Vec::new();
+                    for biome_name in value.split(',') {
+                        let biome = Biome::from_name(biome_name.trim())
+                            .ok_or(format!("unknown biome `{}`", biome_name.trim()))?;
+                        biomes.push(biome);
+                    }
+                }
+                "palette" => {
+                    let (symbol, cell) = parse_palette_entry(value)?;
+                    palette.insert(symbol, cell);
+                }
+                "layout" => in_layout = true,
+                other => return Err(format!("unknown key `{}`", other)),
+            }
+        }
+
+        // trailing empty lines aren't part of the layout
+        while rows.last().is_some_and(|row| row.trim().is_empty()) {
+            rows.pop();
+        }
+        if rows.is_empty() {
+            return Err(format!("structure `{}` has no layout", name));
+        }
+        if spacing <= 0 {
+            return Err(format!("structure `{}` needs a positive spacing", name));
+        }
+
+        let width = rows.iter().map(|row| row.chars().count()).max().unwrap() as i32;
+        let mut cells: Vec<Vec<StructureCell>> = Vec::new();
+        for row in rows {
+            let mut cell_row: Vec<StructureCell> = Vec::new();
+            for symbol in row.chars() {
+                let cell = match symbol {
+                    ' ' => StructureCell::Keep,
+                    '.' => StructureCell::Air,
+                    _ => *palette
+                        .get(&symbol)
+                        .ok_or(format!("`{}` is not in the palette", symbol))?,
+                };
+                cell_row.push(cell);
+            }
+            // short rows are padded so every row has the same width
+            cell_row.resize(width as usize, StructureCell::Keep);
+            cells.push(cell_row);
+        }
+
+        return Ok(Structure {
+            name,
+            placement,
+            spacing,
+            chance,
+            biomes,
+            width,
+            height: cells.len() as i32,
+            cells,
+        });
+    }
+}
+
+fn parse_palette_entry(value: &str) -> Result<(char, StructureCell), String> {
+    let mut parts = value.split_whitespace();
+    let symbol = parts
+        .next()
+        .and_then(|symbol| symbol.chars().next())
+        .ok_or(format!("palette entry `{}` has no symbol", value))?;
+    let color = parts
+        .next()
+        .ok_or(format!("palette entry `{}` has no color", value))?;
+    if !is_hex_color(color) {
+        return Err(format!("invalid color `{}`", color));
+    }
+
+    let mut overlay = BlockOverlay::None;
+    let mut bg = false;
+    let mut block_type = BlockType::Solid;
+    for flag in parts {
+        match flag {
+            "top" => overlay = BlockOverlay::Top,
+            "bg" => bg = true,
+            _ => {
+                block_type =
+                    BlockType::from_name(flag).ok_or(format!("unknown palette flag `{}`", flag))?
+            }
+        }
+    }
+
+    let block = Block::new(block_type, hex_color(color, 1.0), overlay);
+    return Ok((symbol, StructureCell::Block { block, bg }));
+}
+
+pub fn load_structures() -> Vec<Structure> {
+    let sources = [
+        include_str!("./assets/structures/hut.txt"),
+        include_str!("./assets/structures/ruins.txt"),
+        include_str!("./assets/structures/bridge.txt"),
+        include_str!("./assets/structures/dungeon.txt"),
+    ];
+    let mut structures: Vec<Structure> = Vec::new();
+    for source in sources {
+        let structure = Structure::parse(source).unwrap();
+        if structures.iter().any(|other| other.name == structure.name) {
+            panic!("duplicate structure template `{}`", structure.name);
+        }
+        structures.push(structure);
+    }
+    return structures;
+}
@@ -0,0 +1,435 @@
+use crate::biome::Biome;
+use crate::biome::BiomeParams;
+use crate::biome::BiomeSample;
+use crate::biome::Feature;
+use crate::block::Block;
+use crate::block::BlockOverlay;
+use crate::block::BlockType;
+use crate::noise::fractal_noise_1d;
+use crate::noise::fractal_noise_2d;
+use crate::noise::hash;
+use crate::noise::rand_unit;
+use crate::position::GridPos;
+use crate::structure::load_structures;
+use crate::structure::Placement;
+use crate::structure::Structure;
+use crate::structure::StructureCell;
+use crate::utils::hex_color;
+use crate::utils::lerp_color;
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+pub const CHUNK_WIDTH: i32 = 32;
+// average height of the surface, y grows downwards
+pub const SURFACE_LEVEL: i32 = 24;
+pub const WORLD_BOTTOM: i32 = 110;
+pub const BIOME_WIDTH: i32 = 192;
+// how many columns on each side of a biome border get blended
+const BIOME_BLEND: f32 = 32.0;
+// features can hang this far over their own column (tree canopies)
+const FEATURE_REACH: i32 = 2;
+// empty columns and rows kept between structures so they never touch
+const STRUCTURE_PADDING: i32 = 2;
+
+// offsets so every noise layer gets its own pattern from the same seed
+const DIRT_SEED: u32 = 0x68e3_1da4;
+const CAVE_SEED: u32 = 0xb529_7a4d;
+const BIOME_SEED: u32 = 0x1b87_3593;
+const DITHER_SEED: u32 = 0xcc9e_2d51;
+const FEATURE_SEED: u32 = 0xe654_6b64;
+const STRUCTURE_SEED: u32 = 0x85eb_ca6b;
+const CRYSTAL_SEED: u32 = 0xc2b2_ae35;
+// chance for a spot on a cave floor to grow a glowing crystal
+const CRYSTAL_CHANCE: f32 = 0.04;
+const CRYSTAL_COLORS: [&str; 3] = ["#7de3ff", "#c07dff", "#7dffb0"];
+
+pub fn chunk_of(x: i32) -> i32 {
+    return x.div_euclid(CHUNK_WIDTH);
+}
+
+// a structure that passed all of its placement rules, x/y is the top left corner
+struct StructurePlan {
+    index: usize,
+    x: i32,
+    y: i32,
+}
+
+pub struct TerrainGenerator {
+    pub seed: u32,
+    pub structures: Vec<Structure>,
+}
+
+impl TerrainGenerator {
+    pub fn new(seed: u32) -> Self {
+        TerrainGenerator {
+            seed,
+            structures: load_structures(),
+        }
+    }
+
+    fn region_biome(&self, region: i32) -> Biome {
+        let index = hash(self.seed ^ BIOME_SEED, region, 0) as usize % Biome::ALL.len();
+        return Biome::ALL[index];
+    }
+
+    pub fn biome_at(&self, x: i32) -> BiomeSample {
+        let region = x.div_euclid(BIOME_WIDTH);
+        let local = x.rem_euclid(BIOME_WIDTH);
+        let primary = self.region_biome(region);
+
+        // distance to the closest border, in columns
+        let to_left = local as f32 + 0.5;
+        let to_right = (BIOME_WIDTH - local) as f32 - 0.5;
+        let (neighbor, distance) = if to_left < to_right {
+            (region - 1, to_left)
+        } else {
+            (region + 1, to_right)
+        };
+
+        return BiomeSample {
+            primary,
+            secondary: self.region_biome(neighbor),
+            blend: (0.5 * (1.0 - distance / BIOME_BLEND)).max(0.0),
+        };
+    }
+
+    // in blended areas each block randomly takes one of the two palettes
+    fn dithered_params(&self, sample: &BiomeSample, x: i32, y: i32) -> BiomeParams {
+        if rand_unit(self.seed ^ DITHER_SEED, x, y) < sample.blend {
+            return sample.secondary.params();
+        }
+        return sample.primary.params();
+    }
+
+    pub fn surface_height(&self, x: i32) -> i32 {
+        let sample = self.biome_at(x);
+        let amplitude = sample.mix(|params| params.amplitude);
+        let offset = sample.mix(|params| params.height_offset);
+        let hills = fractal_noise_1d(self.seed, x as f32 / 48.0, 4);
+        return SURFACE_LEVEL + (offset + (hills - 0.5) * 2.0 * amplitude) as i32;
+    }
+
+    pub fn sky_colors(&self, x: f32) -> (Color, Color) {
+        let sample = self.biome_at(x as i32);
+        let primary = sample.primary.params();
+        let secondary = sample.secondary.params();
+        return (
+            lerp_color(primary.sky_top_color, secondary.sky_top_color, sample.blend),
+            lerp_color(
+                primary.sky_bottom_color,
+                secondary.sky_bottom_color,
+                sample.blend,
+            ),
+        );
+    }
+
+    fn dirt_depth(&self, x: i32) -> i32 {
+        let noise = fractal_noise_1d(self.seed ^ DIRT_SEED, x as f32 / 16.0, 2);
+        return 3 + (noise * 5.0) as i32;
+    }
+
+    fn is_cave(&self, x: i32, y: i32, surface: i32) -> bool {
+        // keep a crust so caves don't eat the surface
+        if y < surface + 5 || y >= WORLD_BOTTOM - 1 {
+            return false;
+        }
+        let noise = fractal_noise_2d(self.seed ^ CAVE_SEED, x as f32 / 14.0, y as f32 / 9.0, 3);
+        return noise > 0.66;
+    }
+
+    pub fn generate_chunk(&self, chunk_x: i32, blocks: &mut HashMap<GridPos, Block>) {
+        let cave_wall = hex_color("#18191d", 1.0);
+        let start_x = chunk_x * CHUNK_WIDTH;
+        let end_x = start_x + CHUNK_WIDTH;
+
+        for x in start_x..end_x {
+            let sample = self.biome_at(x);
+            let surface = self.surface_height(x);
+            let dirt_depth = self.dirt_depth(x);
+            for y in surface..WORLD_BOTTOM {
+                // carved caves keep a darker wall behind them
+                if self.is_cave(x, y, surface) {
+                    blocks.insert(
+                        GridPos::new(x, y, true),
+                        Block::new(BlockType::Solid, cave_wall, BlockOverlay::None),
+                    );
+                    let on_floor = !self.is_cave(x, y + 1, surface);
+                    if on_floor && rand_unit(self.seed ^ CRYSTAL_SEED, x, y) < CRYSTAL_CHANCE {
+                        let pick = hash(self.seed ^ CRYSTAL_SEED, x, y) as usize;
+                        let color = hex_color(CRYSTAL_COLORS[pick % CRYSTAL_COLORS.len()], 1.0);
+                        blocks.insert(
+                            GridPos::new(x, y, false),
+                            Block::new(BlockType::Crystal, color, BlockOverlay::None),
+                        );
+                    }
+                    continue;
+                }
+                let params = self.dithered_params(&sample, x, y);
+                let block = if y == surface {
+                    Block::new(params.surface_type, params.surface_color, BlockOverlay::Top)
+                } else if y - surface < dirt_depth {
+                    Block::new(
+                        params.subsurface_type,
+                        params.subsurface_color,
+                        BlockOverlay::None,
+                    )
+                } else {
+                    Block::new(BlockType::Solid, params.stone_color, BlockOverlay::None)
+                };
+                blocks.insert(GridPos::new(x, y, false), block);
+            }
+        }
+
+        // features from neighbouring chunks may reach into this one, so look a bit
+        // further out but only ever write inside our own columns. this keeps the
+        // result the same no matter which chunk gets generated first
+        for x in start_x - FEATURE_REACH..end_x + FEATURE_REACH {
+            let sample = self.biome_at(x);
+            let surface = self.surface_height(x);
+            let params = self.dithered_params(&sample, x, surface);
+            if params.feature == Feature::None
+                || rand_unit(self.seed ^ FEATURE_SEED, x, 0) >= params.feature_density
+            {
+                continue;
+            }
+            let height = 3 + (hash(self.seed ^ FEATURE_SEED, x, 1) % 3) as i32;
+            let mut cells: Vec<(GridPos, Color)> = Vec::new();
+            feature_cells(params.feature, x, surface, height, &mut cells);
+            for (pos, color) in cells {
+                if pos.x < start_x || pos.x >= end_x || blocks.contains_key(&pos) {
+                    continue;
+                }
+                blocks.insert(pos, Block::new(BlockType::Solid, color, BlockOverlay::None));
+            }
+        }
+
+        for plan in self.structures_between(start_x, end_x) {
+            self.build_structure(&plan, start_x, end_x, blocks);
+        }
+    }
+
+    fn structure_seed(&self, index: usize) -> u32 {
+        return self.seed ^ STRUCTURE_SEED ^ (index as u32).wrapping_mul(0x9e37_79b9);
+    }
+
+    // every structure kind splits the x axis into cells twice its spacing wide and
+    // may start somewhere in the first half of each, so two of the same kind always
+    // start at least `spacing` columns apart
+    fn structure_candidate(&self, index: usize, cell: i32) -> Option<i32> {
+        let structure = &self.structures[index];
+        let seed = self.structure_seed(index);
+        if rand_unit(seed, cell, 0) >= structure.chance {
+            return None;
+        }
+        let jitter = (hash(seed, cell, 1) % structure.spacing as u32) as i32;
+        return Some(cell * structure.spacing * 2 + jitter);
+    }
+
+    // checks the placement rules for a candidate and works out its height
+    fn plan_structure(&self, index: usize, x: i32) -> Option<StructurePlan> {
+        let structure = &self.structures[index];
+        let center_biome = self.biome_at(x + structure.width / 2).primary;
+        if !structure.biomes.contains(&center_biome) {
+            return None;
+        }
+
+        let surfaces: Vec<i32> = (x..x + structure.width)
+            .map(|column| self.surface_height(column))
+            .collect();
+        let highest = *surfaces.iter().min().unwrap();
+        let lowest = *surfaces.iter().max().unwrap();
+
+        let y = match structure.placement {
+            Placement::Ground => {
+                if lowest - highest > 1 {
+                    return None;
+                }
+                highest - structure.height
+            }
+            Placement::Span => {
+                let left = surfaces[0];
+                let right = surfaces[surfaces.len() - 1];
+                let ends = left.min(right);
+                if (left - right).abs() > 1 || lowest - ends < 3 {
+                    return None;
+                }
+                // the deck replaces the ground at the higher end
+                ends - structure.height + 1
+            }
+            Placement::Underground => {
+                let min_y = lowest + 8;
+                let max_y = WORLD_BOTTOM - structure.height - 2;
+                if min_y > max_y {
+                    return None;
+                }
+                let roll = hash(self.structure_seed(index), x, 2) as i32;
+                min_y + roll.rem_euclid(max_y - min_y + 1)
+            }
+        };
+
+        return Some(StructurePlan { index, x, y });
+    }
+
+    fn plans_overlap(&self, a: &StructurePlan, b: &StructurePlan) -> bool {
+        let a_size = &self.structures[a.index];
+        let b_size = &self.structures[b.index];
+        return a.x - STRUCTURE_PADDING < b.x + b_size.width
+            && a.x + a_size.width + STRUCTURE_PADDING > b.x
+            && a.y - STRUCTURE_PADDING < b.y + b_size.height
+            && a.y + a_size.height + STRUCTURE_PADDING > b.y;
+    }
+
+    // all structures with a column in start_x..end_x. a structure that overlaps one of
+    // an earlier kind is dropped, only plans of earlier kinds are looked at so the
+    // answer never depends on which chunks exist already
+    fn structures_between(&self, start_x: i32, end_x: i32) -> Vec<StructurePlan> {
+        let mut plans: Vec<StructurePlan> = Vec::new();
+        for index in 0..self.structures.len() {
+            for plan in self.raw_plans_between(index, start_x, end_x) {
+                // padded on both sides, plans that end or start within the
+                // padding still overlap
+                let blocked = (0..index).any(|other| {
+                    self.raw_plans_between(
+                        other,
+                        plan.x - STRUCTURE_PADDING,
+                        plan.x + self.structures[index].width + STRUCTURE_PADDING,
+                    )
+                    .iter()
+                    .any(|other_plan| self.plans_overlap(&plan, other_plan))
+                });
+                if !blocked {
+                    plans.push(plan);
+                }
+            }
+        }
+        return plans;
+    }
+
+    fn raw_plans_between(&self, index: usize, start_x: i32, end_x: i32) -> Vec<StructurePlan> {
+        let structure = &self.structures[index];
+        let cell_width = structure.spacing * 2;
+        let first_cell = (start_x - structure.width).div_euclid(cell_width);
+        let last_cell = end_x.div_euclid(cell_width);
+        let mut plans: Vec<StructurePlan> = Vec::new();
+        for cell in first_cell..=last_cell {
+            if let Some(x) = self.structure_candidate(index, cell) {
+                if x + structure.width <= start_x || x >= end_x {
+                    continue;
+                }
+                if let Some(plan) = self.plan_structure(index, x) {
+                    plans.push(plan);
+                }
+            }
+        }
+        return plans;
+    }
+
+    fn build_structure(
+        &self,
+        plan: &StructurePlan,
+        start_x: i32,
+        end_x: i32,
+        blocks: &mut HashMap<GridPos, Block>,
+    ) {
+        let structure = &self.structures[plan.index];
+        for (row, cells) in structure.cells.iter().enumerate() {
+            let y = plan.y + row as i32;
+            for (column, cell) in cells.iter().enumerate() {
+                let x = plan.x + column as i32;
+                if x < start_x || x >= end_x {
+                    continue;
+                }
+                match *cell {
+                    StructureCell::Keep => {}
+                    StructureCell::Air => {
+                        blocks.remove(&GridPos::new(x, y, false));
+                    }
+                    StructureCell::Block { block, bg } => {
+                        if bg {
+                            blocks.remove(&GridPos::new(x, y, false));
+                        }
+                        blocks.insert(GridPos::new(x, y, bg), block);
+                    }
+                }
+            }
+        }
+
+        // grounded structures get their bottom row extended down to fill any gaps
+        if structure.placement != Placement::Ground {
+            return;
+        }
+        let bottom = plan.y + structure.height - 1;
+        for (column, cell) in structure.cells[structure.height as usize - 1]
+            .iter()
+            .enumerate()
+        {
+            let x = plan.x + column as i32;
+            if x < start_x || x >= end_x {
+                continue;
+            }
+            if let StructureCell::Block { block, bg: false } = *cell {
+                let mut y = bottom + 1;
+                while y < WORLD_BOTTOM && !blocks.contains_key(&GridPos::new(x, y, false)) {
+                    blocks.insert(GridPos::new(x, y, false), block);
+                    y += 1;
+                }
+            }
+        }
+    }
+}
+
+// the blocks making up a feature whose base sits on top of `surface` at column `x`.
+// trees are put in the background so they can be walked through
+fn feature_cells(
+    feature: Feature,
+    x: i32,
+    surface: i32,
+    height: i32,
+    cells: &mut Vec<(GridPos, Color)>,
+) {
+    let trunk = hex_color("#3b2a1e", 1.0);
+    let top = surface - height;
+    match feature {
+        Feature::None => {}
+        Feature::Tree => {
+            let leaves = hex_color("#24502f", 1.0);
+            for y in top..surface {
+                cells.push((GridPos::new(x, y, true), trunk));
+            }
+            for dy in -2..=0 {
+                for dx in -FEATURE_REACH..=FEATURE_REACH {
+                    // round off the canopy corners
+                    if dy == -2 && dx.abs() == FEATURE_REACH {
+                        continue;
+                    }
+                    cells.push((GridPos::new(x + dx, top + dy, true), leaves));
+                }
+            }
+        }
+        Feature::Pine => {
+            let needles = hex_color("#1e3b33", 1.0);
+            for y in top..surface {
+                cells.push((GridPos::new(x, y, true), trunk));
+            }
+            // stacked layers getting narrower towards the tip
+            for layer in 0..height {
+                let y = top + 1 - layer;
+                let width = ((height - layer) / 2).min(FEATURE_REACH);
+                for dx in -width..=width {
+                    cells.push((GridPos::new(x + dx, y, true), needles));
+                }
+            }
+        }
+        Feature::Cactus => {
+            let cactus = hex_color("#3d6b35", 1.0);
+            for y in top..surface {
+                cells.push((GridPos::new(x, y, false), cactus));
+            }
+            // a single arm on the taller ones
+            if height > 3 {
+                cells.push((GridPos::new(x + 1, top + 2, false), cactus));
+                cells.push((GridPos::new(x + 1, top + 1, false), cactus));
+            }
+        }
+    }
+}
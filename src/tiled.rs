@@ -0,0 +1,797 @@
+use crate::block::Block;
+use crate::block::BlockOverlay;
+use crate::block::BlockType;
+use crate::clock::START_TIME;
+use crate::export::render_blocks;
+use crate::export::save_png;
+use crate::export::GridRect;
+use crate::position::GridPos;
+use crate::position::SubGridPos;
+use crate::prop::Prop;
+use crate::save::WorldFile;
+use crate::terrain::chunk_of;
+use crate::utils::color_to_hex;
+use crate::utils::hex_color;
+use crate::world::World;
+use crate::BLOCK_SIZE;
+use macroquad::prelude::*;
+use serde_json::json;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// maps made in the Tiled editor, as .tmx (xml) or .tmj (json). every tile is a
+// block, set up with custom properties on the tileset's tiles:
+//
+//   block    block type, solid when missing
+//   color    #rrggbb or #aarrggbb like Tiled writes colors
+//   overlay  overlay name, none when missing
+//   level    how full the cell is, 1 when missing
+//
+// tile layers named `background` or starting with `bg`, or with a `background`
+// bool property, fill the background layer. objects become props, except one of
+// type `player_start` which the player is put on, feet first. exported maps keep
+// `origin_x` and `origin_y` properties so they go back where they came from
+
+pub const PLAYER_START: &str = "player_start";
+// tiles on one row of an exported tileset image
+const TILESET_COLUMNS: usize = 16;
+// Tiled keeps flip flags in the top bits of a gid
+const GID_MASK: u32 = 0x1fff_ffff;
+
+type Properties = Vec<(String, String)>;
+
+// an object from an object layer, in blocks from the top left of the map
+pub struct TiledObject {
+    pub name: String,
+    pub kind: String,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub properties: Properties,
+}
+
+pub struct TiledMap {
+    // where the top left of the map goes in the world, when the map knows
+    pub origin: Option<(i32, i32)>,
+    // every cell the map covers, relative to its top left
+    pub area: GridRect,
+    // relative to the top left of the map, like everything below
+    pub blocks: Vec<(GridPos, Block)>,
+    pub player_start: Option<SubGridPos>,
+    pub objects: Vec<TiledObject>,
+    // size of one tile in map pixels, used for object positions
+    tile_width: f32,
+    tile_height: f32,
+}
+
+impl TiledMap {
+    fn new(width: i32, height: i32, tile_width: f32, tile_height: f32) -> Self {
+        TiledMap {
+            origin: None,
+            area: GridRect::new(
+                GridPos::new(0, 0, false),
+                GridPos::new(width.max(1) - 1, height.max(1) - 1, false),
+            ),
+            blocks: Vec::new(),
+            player_start: None,
+            objects: Vec::new(),
+            tile_width,
+            tile_height,
+        }
+    }
+
+    // picks the format from the extension
+    pub fn read(path: &str) -> Result<TiledMap, String> {
+        let source =
+            fs::read_to_string(path).map_err(|err| format!("couldn't read {}: {}", path, err))?;
+        let extension = Path::new(path)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+        let map = match extension.as_deref() {
+            Some("tmx") => TiledMap::parse_tmx(&source),
+            Some("tmj") | Some("json") => TiledMap::parse_tmj(&source),
+            _ => return Err(format!("{} is not a .tmx or .tmj map", path)),
+        };
+        return map.map_err(|err| format!("{}: {}", path, err));
+    }
+
+    pub fn parse_tmx(source: &str) -> Result<TiledMap, String> {
+        let document = roxmltree::Document::parse(source).map_err(|err| err.to_string())?;
+        let root = document.root_element();
+        if root.tag_name().name() != "map" {
+            return Err("not a Tiled map".to_owned());
+        }
+        let mut map = TiledMap::new(
+            xml_number(root, "width")?,
+            xml_number(root, "height")?,
+            xml_number(root, "tilewidth")?,
+            xml_number(root, "tileheight")?,
+        );
+        map.origin = find_origin(&xml_properties(root))?;
+
+        let mut tiles: HashMap<u32, Block> = HashMap::new();
+        for tileset in root.children().filter(|node| node.has_tag_name("tileset")) {
+            if tileset.has_attribute("source") {
+                return Err("external tilesets aren't supported, embed them in the map".to_owned());
+            }
+            let first_gid: u32 = xml_number(tileset, "firstgid")?;
+            for tile in tileset.children().filter(|node| node.has_tag_name("tile")) {
+                let id: u32 = xml_number(tile, "id")?;
+                let class = tile.attribute("class").or(tile.attribute("type"));
+                tiles.insert(first_gid + id, tile_block(class, &xml_properties(tile))?);
+            }
+        }
+
+        for layer in root.descendants().filter(|node| node.has_tag_name("layer")) {
+            let name = layer.attribute("name").unwrap_or("");
+            let bg = is_background(name, &xml_properties(layer));
+            let data = layer
+                .children()
+                .find(|node| node.has_tag_name("data"))
+                .ok_or(format!("layer `{}` has no data", name))?;
+            let encoding = data.attribute("encoding");
+
+            // finite maps keep tiles right in the data, infinite ones in chunks
+            let mut parts = vec![(data, 0, 0, xml_number(layer, "width")?)];
+            let chunks: Vec<_> = data
+                .children()
+                .filter(|node| node.has_tag_name("chunk"))
+                .collect();
+            if !chunks.is_empty() {
+                parts.clear();
+                for chunk in chunks {
+                    parts.push((
+                        chunk,
+                        xml_number(chunk, "x")?,
+                        xml_number(chunk, "y")?,
+                        xml_number(chunk, "width")?,
+                    ));
+                }
+            }
+
+            for (node, offset_x, offset_y, width) in parts {
+                let gids: Vec<u32> = match encoding {
+                    Some("csv") => parse_csv(node.text().unwrap_or(""))?,
+                    None => node
+                        .children()
+                        .filter(|child| child.has_tag_name("tile"))
+                        .map(|tile| xml_number(tile, "gid").unwrap_or(0))
+                        .collect(),
+                    Some(other) => {
+                        return Err(format!("{} tile data isn't supported, use csv", other))
+                    }
+                };
+                map.add_tiles(&gids, offset_x, offset_y, width, bg, &tiles)?;
+            }
+        }
+
+        // tiles can have object groups of their own for collision shapes, skip those
+        let groups = root.descendants().filter(|node| {
+            node.has_tag_name("objectgroup")
+                && !node
+                    .parent()
+                    .is_some_and(|parent| parent.has_tag_name("tile"))
+        });
+        let objects =
+            groups.flat_map(|group| group.children().filter(|node| node.has_tag_name("object")));
+        for object in objects {
+            let point = object.children().any(|node| node.has_tag_name("point"));
+            map.add_object(
+                TiledObject {
+                    name: object.attribute("name").unwrap_or("").to_owned(),
+                    kind: object
+                        .attribute("type")
+                        .or(object.attribute("class"))
+                        .unwrap_or("")
+                        .to_owned(),
+                    x: xml_number(object, "x")?,
+                    y: xml_number(object, "y")?,
+                    width: xml_number(object, "width").unwrap_or(0.0),
+                    height: xml_number(object, "height").unwrap_or(0.0),
+                    properties: xml_properties(object),
+                },
+                point,
+            );
+        }
+        return Ok(map);
+    }
+
+    pub fn parse_tmj(source: &str) -> Result<TiledMap, String> {
+        let root: Value = serde_json::from_str(source).map_err(|err| err.to_string())?;
+        if root["type"] != "map" {
+            return Err("not a Tiled map".to_owned());
+        }
+        let mut map = TiledMap::new(
+            json_number(&root, "width")? as i32,
+            json_number(&root, "height")? as i32,
+            json_number(&root, "tilewidth")? as f32,
+            json_number(&root, "tileheight")? as f32,
+        );
+        map.origin = find_origin(&json_properties(&root))?;
+
+        let mut tiles: HashMap<u32, Block> = HashMap::new();
+        for tileset in json_array(&root, "tilesets") {
+            if tileset.get("source").is_some() {
+                return Err("external tilesets aren't supported, embed them in the map".to_owned());
+            }
+            let first_gid = json_number(tileset, "firstgid")? as u32;
+            for tile in json_array(tileset, "tiles") {
+                let id = json_number(tile, "id")? as u32;
+                let class = tile["class"].as_str().or(tile["type"].as_str());
+                tiles.insert(first_gid + id, tile_block(class, &json_properties(tile))?);
+            }
+        }
+
+        let mut layers: Vec<&Value> = Vec::new();
+        json_layers(&root, &mut layers);
+        for layer in layers {
+            let name = layer["name"].as_str().unwrap_or("");
+            match layer["type"].as_str() {
+                Some("tilelayer") => {
+                    if layer
+                        .get("encoding")
+                        .is_some_and(|encoding| *encoding != "csv")
+                    {
+                        return Err("compressed tile data isn't supported, use csv".to_owned());
+                    }
+                    let bg = is_background(name, &json_properties(layer));
+                    let mut parts = vec![layer];
+                    if layer.get("chunks").is_some() {
+                        parts = json_array(layer, "chunks").iter().collect();
+                    }
+                    for part in parts {
+                        let gids: Vec<u32> = json_array(part, "data")
+                            .iter()
+                            .map(|gid| gid.as_u64().unwrap_or(0) as u32)
+                            .collect();
+                        let offset_x = part["x"].as_i64().unwrap_or(0) as i32;
+                        let offset_y = part["y"].as_i64().unwrap_or(0) as i32;
+                        let width = json_number(part, "width")? as i32;
+                        map.add_tiles(&gids, offset_x, offset_y, width, bg, &tiles)?;
+                    }
+                }
+                Some("objectgroup") => {
+                    for object in json_array(layer, "objects") {
+                        let kind = object["type"].as_str().or(object["class"].as_str());
+                        map.add_object(
+                            TiledObject {
+                                name: object["name"].as_str().unwrap_or("").to_owned(),
+                                kind: kind.unwrap_or("").to_owned(),
+                                x: json_number(object, "x")? as f32,
+                                y: json_number(object, "y")? as f32,
+                                width: object["width"].as_f64().unwrap_or(0.0) as f32,
+                                height: object["height"].as_f64().unwrap_or(0.0) as f32,
+                                properties: json_properties(object),
+                            },
+                            object["point"] == true,
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+        return Ok(map);
+    }
+
+    // one row-major run of gids, 0 is an empty cell
+    fn add_tiles(
+        &mut self,
+        gids: &[u32],
+        offset_x: i32,
+        offset_y: i32,
+        width: i32,
+        bg: bool,
+        tiles: &HashMap<u32, Block>,
+    ) -> Result<(), String> {
+        if width <= 0 {
+            return Err("tile data with no width".to_owned());
+        }
+        for (index, gid) in gids.iter().enumerate() {
+            let gid = gid & GID_MASK;
+            if gid == 0 {
+                continue;
+            }
+            let x = offset_x + index as i32 % width;
+            let y = offset_y + index as i32 / width;
+            // tiles without properties are plain solid blocks
+            let block = tiles.get(&gid).copied().unwrap_or_else(default_block);
+            self.blocks.push((GridPos::new(x, y, bg), block));
+            self.area.min_x = self.area.min_x.min(x);
+            self.area.min_y = self.area.min_y.min(y);
+            self.area.max_x = self.area.max_x.max(x);
+            self.area.max_y = self.area.max_y.max(y);
+        }
+        return Ok(());
+    }
+
+    // `object` is still in map pixels, points have no size
+    fn add_object(&mut self, mut object: TiledObject, point: bool) {
+        if point {
+            object.width = 0.0;
+            object.height = 0.0;
+        }
+        object.x /= self.tile_width;
+        object.y /= self.tile_height;
+        object.width /= self.tile_width;
+        object.height /= self.tile_height;
+        if object.kind == PLAYER_START {
+            // the object marks the player's feet, the bottom middle of a 3x3 box
+            self.player_start = Some(SubGridPos {
+                x: object.x + object.width / 2.0 - 1.5,
+                y: object.y + object.height - 3.0,
+            });
+            return;
+        }
+        self.objects.push(object);
+    }
+
+    // every block in the world, with the top left block as the origin
+    pub fn from_world(world: &World) -> Option<TiledMap> {
+        return TiledMap::from_blocks(&world.blocks, world.player.pos);
+    }
+
+    pub fn from_blocks(blocks: &HashMap<GridPos, Block>, player: SubGridPos) -> Option<TiledMap> {
+        let area = GridRect::around(blocks)?;
+        let origin = GridPos::new(area.min_x, area.min_y, false);
+        let mut map = TiledMap::new(area.width(), area.height(), BLOCK_SIZE, BLOCK_SIZE);
+        map.origin = Some((area.min_x, area.min_y));
+        map.blocks = blocks
+            .iter()
+            .map(|(pos, block)| (*pos - origin, *block))
+            .collect();
+        map.player_start = Some(SubGridPos {
+            x: player.x - area.min_x as f32,
+            y: player.y - area.min_y as f32,
+        });
+        return Some(map);
+    }
+
+    // the map as a world file without a window, for the command line. world
+    // files don't keep props so the objects are dropped, and every chunk the map
+    // touches counts as generated so terrain never grows into it
+    pub fn into_world_file(self, seed: u32) -> WorldFile {
+        let (origin_x, origin_y) = self.origin.unwrap_or((0, 0));
+        let blocks: HashMap<GridPos, Block> = self
+            .blocks
+            .into_iter()
+            .map(|(pos, block)| (pos + GridPos::new(origin_x, origin_y, pos.bg), block))
+            .collect();
+        let player = match self.player_start {
+            Some(start) => SubGridPos {
+                x: start.x + origin_x as f32,
+                y: start.y + origin_y as f32,
+            },
+            // standing on the top left corner
+            None => SubGridPos {
+                x: origin_x as f32,
+                y: (origin_y - 3) as f32,
+            },
+        };
+        let first = chunk_of(self.area.min_x + origin_x);
+        let last = chunk_of(self.area.max_x + origin_x);
+        return WorldFile {
+            seed,
+            time: START_TIME,
+            player,
+            chunks: (first..=last).collect(),
+            blocks,
+        };
+    }
+
+    // puts the map into the world, replacing everything in its area. `fallback`
+    // is where its top left goes when the map has no origin of its own
+    pub fn apply(self, world: &mut World, fallback: (i32, i32)) {
+        let (origin_x, origin_y) = self.origin.unwrap_or(fallback);
+        let shift = GridPos::new(origin_x, origin_y, false);
+
+        for pos in self.area.cells() {
+            let pos = pos + GridPos::new(shift.x, shift.y, pos.bg);
+            world.remove_block(pos);
+        }
+        world.place_blocks(
+            self.blocks
+                .into_iter()
+                .map(|(pos, block)| (pos + GridPos::new(shift.x, shift.y, pos.bg), block))
+                .collect(),
+        );
+
+        for object in self.objects {
+            let color = object
+                .properties
+                .iter()
+                .find(|(name, _)| name == "color")
+                .and_then(|(_, value)| parse_color(value))
+                .unwrap_or(Color::new(0.9, 0.75, 0.3, 0.5));
+            world.add_entity(Box::new(Prop {
+                id: 0,
+                pos: SubGridPos {
+                    x: object.x + shift.x as f32,
+                    y: object.y + shift.y as f32,
+                },
+                width: object.width.max(1.0),
+                height: object.height.max(1.0),
+                color,
+                name: object.name,
+                class: object.kind,
+                properties: object.properties,
+            }));
+        }
+
+        if let Some(start) = self.player_start {
+            let pos = SubGridPos {
+                x: start.x + shift.x as f32,
+                y: start.y + shift.y as f32,
+            };
+            world.player.respawn(pos);
+            world.camera.snap_to(pos);
+        }
+    }
+
+    // writes the map as .tmj with its tileset image next to it, named like the
+    // map with `-tiles.png` on the end
+    pub fn write_tmj(&self, path: &str) -> Result<(), String> {
+        let stem = Path::new(path).with_extension("");
+        let image_path = format!("{}-tiles.png", stem.to_string_lossy());
+        let file_name = stem
+            .file_name()
+            .ok_or(format!("{} is not a file name", path))?;
+        let image_name = format!("{}-tiles.png", file_name.to_string_lossy());
+
+        // every different looking block becomes one tile
+        let mut palette: Vec<Block> = Vec::new();
+        let mut palette_index: HashMap<String, u32> = HashMap::new();
+        let width = self.area.width();
+        let height = self.area.height();
+        let mut layers = [
+            vec![0; (width * height) as usize],
+            vec![0; (width * height) as usize],
+        ];
+        for (pos, block) in &self.blocks {
+            let key = block_key(block);
+            let gid = *palette_index.entry(key).or_insert_with(|| {
+                palette.push(*block);
+                palette.len() as u32
+            });
+            let index = (pos.y - self.area.min_y) * width + pos.x - self.area.min_x;
+            layers[if pos.bg { 0 } else { 1 }][index as usize] = gid;
+        }
+
+        let sheet = tileset_image(&palette)?;
+        save_png(&sheet, &image_path)?;
+
+        let tiles: Vec<Value> = palette
+            .iter()
+            .enumerate()
+            .map(|(id, block)| {
+                json!({
+                    "id": id,
+                    "properties": [
+                        { "name": "block", "type": "string", "value": block.block_type.name() },
+                        { "name": "color", "type": "color", "value": tiled_color(block.color) },
+                        { "name": "overlay", "type": "string", "value": block.overlay.name() },
+                        { "name": "level", "type": "float", "value": block.level },
+                    ],
+                })
+            })
+            .collect();
+
+        // the data starts at the top left of the area, everything else moves with it
+        let (min_x, min_y) = (self.area.min_x as f32, self.area.min_y as f32);
+        let mut objects: Vec<Value> = Vec::new();
+        if let Some(start) = self.player_start {
+            objects.push(json!({
+                "id": 1,
+                "name": "",
+                "type": PLAYER_START,
+                "point": true,
+                "x": (start.x - min_x + 1.5) * self.tile_width,
+                "y": (start.y - min_y + 3.0) * self.tile_height,
+                "width": 0,
+                "height": 0,
+                "rotation": 0,
+                "visible": true,
+            }));
+        }
+        for object in &self.objects {
+            let properties: Vec<Value> = object
+                .properties
+                .iter()
+                .map(|(name, value)| json!({ "name": name, "type": "string", "value": value }))
+                .collect();
+            objects.push(json!({
+                "id": objects.len() + 1,
+                "name": object.name,
+                "type": object.kind,
+                "x": (object.x - min_x) * self.tile_width,
+                "y": (object.y - min_y) * self.tile_height,
+                "width": object.width * self.tile_width,
+                "height": object.height * self.tile_height,
+                "rotation": 0,
+                "visible": true,
+                "properties": properties,
+            }));
+        }
+
+        let (origin_x, origin_y) = self.origin.unwrap_or((0, 0));
+        let tile_layer = |id: u32, name: &str, data: &[u32], bg: bool| {
+            json!({
+                "id": id,
+                "type": "tilelayer",
+                "name": name,
+                "x": 0,
+                "y": 0,
+                "width": width,
+                "height": height,
+                "opacity": 1,
+                "visible": true,
+                "data": data,
+                "properties": [{ "name": "background", "type": "bool", "value": bg }],
+            })
+        };
+        let map = json!({
+            "type": "map",
+            "version": "1.10",
+            "tiledversion": "1.10.2",
+            "orientation": "orthogonal",
+            "renderorder": "right-down",
+            "infinite": false,
+            "compressionlevel": -1,
+            "width": width,
+            "height": height,
+            "tilewidth": self.tile_width as i32,
+            "tileheight": self.tile_height as i32,
+            "nextlayerid": 4,
+            "nextobjectid": objects.len() + 1,
+            "properties": [
+                { "name": "origin_x", "type": "int", "value": origin_x + self.area.min_x },
+                { "name": "origin_y", "type": "int", "value": origin_y + self.area.min_y },
+            ],
+            "layers": [
+                tile_layer(1, "background", &layers[0], true),
+                tile_layer(2, "foreground", &layers[1], false),
+                {
+                    "id": 3,
+                    "type": "objectgroup",
+                    "name": "objects",
+                    "draworder": "topdown",
+                    "x": 0,
+                    "y": 0,
+                    "opacity": 1,
+                    "visible": true,
+                    "objects": objects,
+                },
+            ],
+            "tilesets": [{
+                "firstgid": 1,
+                "name": "blocks",
+                "tilewidth": BLOCK_SIZE as i32,
+                "tileheight": BLOCK_SIZE as i32,
+                "tilecount": palette.len(),
+                "columns": TILESET_COLUMNS.min(palette.len().max(1)),
+                "margin": 0,
+                "spacing": 0,
+                "image": image_name,
+                "imagewidth": sheet.width,
+                "imageheight": sheet.height,
+                "tiles": tiles,
+            }],
+        });
+        let text = serde_json::to_string(&map).unwrap();
+        return fs::write(path, text).map_err(|err| format!("couldn't write {}: {}", path, err));
+    }
+}
+
+pub fn import_map(world: &mut World, path: &str, fallback: (i32, i32)) -> Result<(), String> {
+    TiledMap::read(path)?.apply(world, fallback);
+    return Ok(());
+}
+
+pub fn export_map(world: &World, path: &str) -> Result<(), String> {
+    let map = TiledMap::from_world(world).ok_or("there are no blocks to export")?;
+    return map.write_tmj(path);
+}
+
+fn default_block() -> Block {
+    return Block::new(
+        BlockType::Solid,
+        hex_color("#808080", 1.0),
+        BlockOverlay::None,
+    );
+}
+
+// the block a tile stands for. a `class` that names a block type is used when
+// there's no block property, other classes are left for Tiled's own use
+fn tile_block(class: Option<&str>, properties: &Properties) -> Result<Block, String> {
+    let mut block = default_block();
+    if let Some(block_type) = class.and_then(BlockType::from_name) {
+        block.block_type = block_type;
+    }
+    for (name, value) in properties {
+        match name.as_str() {
+            "block" => {
+                block.block_type =
+                    BlockType::from_name(value).ok_or(format!("unknown block type `{}`", value))?
+            }
+            "color" => {
+                block.color = parse_color(value).ok_or(format!("invalid color `{}`", value))?
+            }
+            "overlay" => {
+                block.overlay =
+                    BlockOverlay::from_name(value).ok_or(format!("unknown overlay `{}`", value))?
+            }
+            "level" => {
+                block.level = value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|level| level.is_finite())
+                    .ok_or(format!("invalid level `{}`", value))?
+                    .clamp(0.0, 1.0)
+            }
+            _ => {}
+        }
+    }
+    return Ok(block);
+}
+
+fn is_background(name: &str, properties: &Properties) -> bool {
+    if let Some((_, value)) = properties.iter().find(|(name, _)| name == "background") {
+        return value == "true";
+    }
+    let name = name.to_lowercase();
+    return name == "background" || name.starts_with("bg");
+}
+
+fn find_origin(properties: &Properties) -> Result<Option<(i32, i32)>, String> {
+    let get = |key: &str| properties.iter().find(|(name, _)| name == key);
+    let (Some((_, x)), Some((_, y))) = (get("origin_x"), get("origin_y")) else {
+        return Ok(None);
+    };
+    let parse = |value: &String| {
+        value
+            .parse::<i32>()
+            .map_err(|_| format!("invalid origin `{}`", value))
+    };
+    return Ok(Some((parse(x)?, parse(y)?)));
+}
+
+// Tiled writes colors as #aarrggbb, #rrggbb is fully opaque
+fn parse_color(value: &str) -> Option<Color> {
+    let digits = value.strip_prefix('#')?;
+    // checked first so slicing below never lands inside a multi-byte character
+    if !digits.chars().all(|digit| digit.is_ascii_hexdigit()) {
+        return None;
+    }
+    let (alpha, rgb) = match digits.len() {
+        8 => (u8::from_str_radix(&digits[0..2], 16).ok()?, &digits[2..]),
+        6 => (255, digits),
+        _ => return None,
+    };
+    return Some(hex_color(&format!("#{}", rgb), alpha as f32 / 255.0));
+}
+
+fn tiled_color(color: Color) -> String {
+    let alpha = (color.a.clamp(0.0, 1.0) * 255.0).round() as u8;
+    return format!("#{:02x}{}", alpha, &color_to_hex(color)[1..]);
+}
+
+// everything that makes two blocks look different
+fn block_key(block: &Block) -> String {
+    return format!(
+        "{} {} {} {}",
+        block.block_type.name(),
+        tiled_color(block.color),
+        block.overlay.name(),
+        block.level
+    );
+}
+
+// one tile per palette block, drawn the same way world exports are
+fn tileset_image(palette: &[Block]) -> Result<Image, String> {
+    let size = BLOCK_SIZE as u32;
+    let columns = TILESET_COLUMNS.min(palette.len().max(1));
+    let rows = palette.len().div_ceil(columns).max(1);
+    let mut sheet = Image::gen_image_color(
+        (columns as u32 * size) as u16,
+        (rows as u32 * size) as u16,
+        BLANK,
+    );
+    let cell = GridPos::new(0, 0, false);
+    for (index, block) in palette.iter().enumerate() {
+        let single = HashMap::from([(cell, *block)]);
+        let tile = render_blocks(&single, GridRect::new(cell, cell), size, BLANK)?;
+        let left = (index % columns) as u32 * size;
+        let top = (index / columns) as u32 * size;
+        for y in 0..size {
+            for x in 0..size {
+                sheet.set_pixel(left + x, top + y, tile.get_pixel(x, y));
+            }
+        }
+    }
+    return Ok(sheet);
+}
+
+fn parse_csv(text: &str) -> Result<Vec<u32>, String> {
+    return text
+        .split(',')
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            value
+                .parse::<u32>()
+                .map_err(|_| format!("invalid tile `{}`", value))
+        })
+        .collect();
+}
+
+fn xml_number<T: std::str::FromStr>(node: roxmltree::Node, name: &str) -> Result<T, String> {
+    let value =
+        node.attribute(name)
+            .ok_or(format!("<{}> has no {}", node.tag_name().name(), name))?;
+    return value
+        .parse()
+        .map_err(|_| format!("invalid {} `{}`", name, value));
+}
+
+fn xml_properties(node: roxmltree::Node) -> Properties {
+    let Some(properties) = node
+        .children()
+        .find(|child| child.has_tag_name("properties"))
+    else {
+        return Vec::new();
+    };
+    return properties
+        .children()
+        .filter(|child| child.has_tag_name("property"))
+        .map(|property| {
+            let name = property.attribute("name").unwrap_or("");
+            // long strings are written as the element's text instead
+            let value = property
+                .attribute("value")
+                .or(property.text())
+                .unwrap_or("");
+            (name.to_owned(), value.to_owned())
+        })
+        .collect();
+}
+
+fn json_number(value: &Value, name: &str) -> Result<f64, String> {
+    return value[name]
+        .as_f64()
+        .ok_or(format!("missing or invalid `{}`", name));
+}
+
+// an empty list when the key is missing
+fn json_array<'a>(value: &'a Value, name: &str) -> &'a [Value] {
+    return value[name]
+        .as_array()
+        .map(|array| array.as_slice())
+        .unwrap_or(&[]);
+}
+
+// every layer below `value` in drawing order, with group layers opened up
+fn json_layers<'a>(value: &'a Value, layers: &mut Vec<&'a Value>) {
+    for layer in json_array(value, "layers") {
+        if layer["type"] == "group" {
+            json_layers(layer, layers);
+        } else {
+            layers.push(layer);
+        }
+    }
+}
+
+fn json_properties(value: &Value) -> Properties {
+    return json_array(value, "properties")
+        .iter()
+        .map(|property| {
+            let name = property["name"].as_str().unwrap_or("").to_owned();
+            let value = match &property["value"] {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            (name, value)
+        })
+        .collect();
+}
@@ -0,0 +1,613 @@
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+// colors and sizes every widget is drawn with, sizes are in logical pixels
+// before the ui scale
+#[derive(Clone)]
+pub struct Theme {
+    pub text: Color,
+    pub dim_text: Color,
+    pub panel: Color,
+    pub widget: Color,
+    pub hovered: Color,
+    pub accent: Color,
+    pub border: Color,
+    pub font_size: f32,
+    pub heading_size: f32,
+    pub item_height: f32,
+    pub padding: f32,
+    pub spacing: f32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            text: WHITE,
+            dim_text: Color::new(1.0, 1.0, 1.0, 0.5),
+            panel: Color::new(0.0, 0.0, 0.0, 0.6),
+            widget: Color::new(1.0, 1.0, 1.0, 0.08),
+            hovered: Color::new(1.0, 1.0, 1.0, 0.25),
+            accent: Color::new(0.45, 0.75, 1.0, 1.0),
+            border: Color::new(1.0, 1.0, 1.0, 0.4),
+            font_size: 18.0,
+            heading_size: 40.0,
+            item_height: 30.0,
+            padding: 10.0,
+            spacing: 6.0,
+        }
+    }
+}
+
+// where on the screen a panel sits
+#[derive(Clone, Copy)]
+pub enum Anchor {
+    // centered along the top edge
+    Top,
+    Center,
+}
+
+impl Anchor {
+    // the top left corner of something `size` big, `margin` away from the edges
+    fn place(&self, size: Vec2, margin: f32) -> Vec2 {
+        let x = (screen_width() - size.x) / 2.0;
+        let y = match self {
+            Anchor::Top => margin,
+            Anchor::Center => (screen_height() - size.y) / 2.0,
+        };
+        return vec2(x, y);
+    }
+}
+
+// widgets go down a column, filling its width, or along a row at their own width
+struct Layout {
+    origin: Vec2,
+    cursor: Vec2,
+    width: f32,
+    row: bool,
+    // how far down and across anything has been put so far
+    extent: Vec2,
+}
+
+// a small immediate mode ui: widgets are drawn and report what happened to them
+// in the same call, with only focus, dragging, scrolling and panel sizes kept
+// between frames. the keyboard moves focus with the arrows or tab, and enter
+// activates whatever is focused
+pub struct Ui {
+    pub theme: Theme,
+    // on top of the screen's dpi scale, which only makes text sharper
+    pub scale: f32,
+    font: Option<Font>,
+    layouts: Vec<Layout>,
+    // interactive widgets so far this frame
+    widget_count: usize,
+    focus: Option<usize>,
+    dragging: Option<usize>,
+    // first visible row of every scroll list
+    scroll: HashMap<String, f32>,
+    // panel heights from last frame so their background can go down first
+    panel_sizes: HashMap<String, f32>,
+    tooltip: Option<String>,
+    last_hovered: bool,
+    // input read once at the start of the frame
+    keyboard: bool,
+    typed: Vec<char>,
+    // focus movement nothing used up yet
+    nav: i32,
+    mouse: Vec2,
+    last_mouse: Vec2,
+    clicked: bool,
+}
+
+impl Ui {
+    pub fn new(font: Option<Font>) -> Self {
+        Ui {
+            theme: Theme::default(),
+            scale: 1.0,
+            font,
+            layouts: Vec::new(),
+            widget_count: 0,
+            focus: None,
+            dragging: None,
+            scroll: HashMap::new(),
+            panel_sizes: HashMap::new(),
+            tooltip: None,
+            last_hovered: false,
+            keyboard: false,
+            typed: Vec::new(),
+            nav: 0,
+            mouse: Vec2::ZERO,
+            last_mouse: Vec2::ZERO,
+            clicked: false,
+        }
+    }
+
+    // `keyboard` is false while something else, like the game, has the keyboard
+    pub fn begin(&mut self, keyboard: bool) {
+        self.widget_count = 0;
+        self.tooltip = None;
+        self.last_hovered = false;
+        self.keyboard = keyboard;
+        self.last_mouse = self.mouse;
+        self.mouse = Vec2::from(mouse_position());
+        self.clicked = is_mouse_button_pressed(MouseButton::Left);
+        if !is_mouse_button_down(MouseButton::Left) {
+            self.dragging = None;
+        }
+        self.layouts.clear();
+        self.layouts.push(Layout {
+            origin: Vec2::ZERO,
+            cursor: Vec2::ZERO,
+            width: screen_width(),
+            row: false,
+            extent: Vec2::ZERO,
+        });
+
+        self.typed.clear();
+        self.nav = 0;
+        if !keyboard {
+            return;
+        }
+        self.typed = std::iter::from_fn(get_char_pressed).collect();
+        let back = is_key_down(KeyCode::LeftShift);
+        if is_key_pressed(KeyCode::Down) || (is_key_pressed(KeyCode::Tab) && !back) {
+            self.nav = 1;
+        }
+        if is_key_pressed(KeyCode::Up) || (is_key_pressed(KeyCode::Tab) && back) {
+            self.nav = -1;
+        }
+    }
+
+    // moves focus if nothing used the arrow keys and draws the tooltip on top
+    pub fn end(&mut self) {
+        let count = self.widget_count;
+        if count > 0 && self.nav != 0 {
+            self.focus = Some(match self.focus {
+                Some(focus) => (focus as i32 + self.nav).rem_euclid(count as i32) as usize,
+                None if self.nav > 0 => 0,
+                None => count - 1,
+            });
+        }
+        if let Some(focus) = self.focus {
+            if focus >= count.max(1) {
+                self.focus = None;
+            }
+        }
+
+        if let Some(tooltip) = self.tooltip.take() {
+            let size = self.px(self.theme.font_size) * 0.8;
+            let padding = self.px(self.theme.padding) * 0.5;
+            let width = self.text_width(&tooltip, size) + padding * 2.0;
+            let height = size + padding * 2.0;
+            let x = (self.mouse.x + 14.0).min(screen_width() - width);
+            let y = (self.mouse.y + 18.0).min(screen_height() - height);
+            draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.85));
+            draw_rectangle_lines(x, y, width, height, 1.0, self.theme.border);
+            self.text(
+                &tooltip,
+                vec2(x + padding, y + padding),
+                size,
+                self.theme.text,
+            );
+        }
+    }
+
+    // the first widget is focused, for when a new menu opens
+    pub fn reset_focus(&mut self) {
+        self.focus = Some(0);
+        self.dragging = None;
+    }
+
+    // a theme size in screen pixels
+    fn px(&self, size: f32) -> f32 {
+        return size * self.scale;
+    }
+
+    // text is rasterized at the screen's real resolution and scaled back down,
+    // so it stays sharp on high dpi screens
+    fn text_params(&self, size: f32, color: Color) -> TextParams<'_> {
+        let dpi = screen_dpi_scale();
+        return TextParams {
+            font: self.font.as_ref(),
+            font_size: (size * dpi).round() as u16,
+            font_scale: 1.0 / dpi,
+            color,
+            ..Default::default()
+        };
+    }
+
+    fn text_width(&self, text: &str, size: f32) -> f32 {
+        let dpi = screen_dpi_scale();
+        let font_size = (size * dpi).round() as u16;
+        return measure_text(text, self.font.as_ref(), font_size, 1.0 / dpi).width;
+    }
+
+    // `top_left` is the top of the line, not the baseline
+    fn text(&self, text: &str, top_left: Vec2, size: f32, color: Color) {
+        let baseline = top_left.y + size * 0.8;
+        let mut shadow = self.text_params(size, BLACK);
+        shadow.color.a = color.a;
+        draw_text_ex(text, top_left.x + 1.0, baseline + 1.0, shadow);
+        draw_text_ex(text, top_left.x, baseline, self.text_params(size, color));
+    }
+
+    fn text_centered(&self, text: &str, rect: Rect, size: f32, color: Color) {
+        let width = self.text_width(text, size);
+        let top_left = vec2(
+            rect.x + (rect.w - width) / 2.0,
+            rect.y + (rect.h - size) / 2.0,
+        );
+        self.text(text, top_left, size, color);
+    }
+
+    fn layout(&mut self) -> &mut Layout {
+        return self.layouts.last_mut().unwrap();
+    }
+
+    // room for the next widget. in a column it's as wide as the column and
+    // `width` is ignored
+    fn allocate(&mut self, width: f32, height: f32) -> Rect {
+        let spacing = self.px(self.theme.spacing);
+        let layout = self.layout();
+        let rect = if layout.row {
+            Rect::new(layout.cursor.x, layout.cursor.y, width, height)
+        } else {
+            Rect::new(layout.cursor.x, layout.cursor.y, layout.width, height)
+        };
+        if layout.row {
+            layout.cursor.x += rect.w + spacing;
+        } else {
+            layout.cursor.y += rect.h + spacing;
+        }
+        layout.extent.x = layout.extent.x.max(rect.right() - layout.origin.x);
+        layout.extent.y = layout.extent.y.max(rect.bottom() - layout.origin.y);
+        return rect;
+    }
+
+    // gives a widget its focus index and works out whether it's hovered,
+    // focused and activated by a click or enter
+    fn interact(&mut self, rect: Rect) -> (usize, bool, bool) {
+        let id = self.widget_count;
+        self.widget_count += 1;
+        let hovered = rect.contains(self.mouse);
+        if hovered && self.mouse != self.last_mouse {
+            self.focus = Some(id);
+        }
+        self.last_hovered = hovered;
+        let focused = self.focus == Some(id);
+        let activated = (hovered && self.clicked)
+            || (focused
+                && self.keyboard
+                && (is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter)));
+        if hovered && self.clicked {
+            self.focus = Some(id);
+        }
+        return (id, focused || hovered, activated);
+    }
+
+    fn widget_background(&self, rect: Rect, highlighted: bool) {
+        let color = if highlighted {
+            self.theme.hovered
+        } else {
+            self.theme.widget
+        };
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, color);
+    }
+
+    // a box of its own somewhere on the screen, widgets go down it until end_panel.
+    // `id` has to stay the same between frames
+    pub fn begin_panel(&mut self, id: &str, anchor: Anchor, width: f32) {
+        let padding = self.px(self.theme.padding);
+        let width = self.px(width);
+        let height = self.panel_sizes.get(id).copied().unwrap_or(0.0);
+        let size = vec2(width + padding * 2.0, height + padding * 2.0);
+        let corner = anchor.place(size, padding);
+        draw_rectangle(corner.x, corner.y, size.x, size.y, self.theme.panel);
+        let origin = corner + vec2(padding, padding);
+        self.layouts.push(Layout {
+            origin,
+            cursor: origin,
+            width,
+            row: false,
+            extent: Vec2::ZERO,
+        });
+        self.panel_sizes.insert(id.to_owned(), height);
+    }
+
+    pub fn end_panel(&mut self, id: &str) {
+        let layout = self.layouts.pop().unwrap();
+        self.panel_sizes.insert(id.to_owned(), layout.extent.y);
+    }
+
+    // widgets go next to each other until end_row
+    pub fn begin_row(&mut self) {
+        let parent = self.layout();
+        let origin = parent.cursor;
+        let width = parent.width;
+        self.layouts.push(Layout {
+            origin,
+            cursor: origin,
+            width,
+            row: true,
+            extent: Vec2::ZERO,
+        });
+    }
+
+    pub fn end_row(&mut self) {
+        let row = self.layouts.pop().unwrap();
+        self.allocate(row.extent.x, row.extent.y);
+    }
+
+    pub fn space(&mut self, height: f32) {
+        let height = self.px(height);
+        self.allocate(0.0, height);
+    }
+
+    pub fn label(&mut self, text: &str) {
+        let size = self.px(self.theme.font_size);
+        let width = self.text_width(text, size);
+        let rect = self.allocate(width, size);
+        self.text(text, rect.point(), size, self.theme.text);
+    }
+
+    // big centered text, for the top of a menu
+    pub fn heading(&mut self, text: &str) {
+        let size = self.px(self.theme.heading_size);
+        let width = self.text_width(text, size);
+        let rect = self.allocate(width, size * 1.2);
+        self.text_centered(text, rect, size, self.theme.text);
+    }
+
+    // shows `text` next to the mouse while the widget before it is hovered
+    pub fn tooltip(&mut self, text: &str) {
+        if self.last_hovered {
+            self.tooltip = Some(text.to_owned());
+        }
+    }
+
+    pub fn button(&mut self, text: &str) -> bool {
+        let size = self.px(self.theme.font_size);
+        let padding = self.px(self.theme.padding);
+        let width = self.text_width(text, size) + padding * 2.0;
+        let rect = self.allocate(width, self.px(self.theme.item_height));
+        let (_, highlighted, activated) = self.interact(rect);
+        self.widget_background(rect, highlighted);
+        self.text_centered(text, rect, size, self.theme.text);
+        return activated;
+    }
+
+    // returns true when it was toggled
+    pub fn checkbox(&mut self, text: &str, value: &mut bool) -> bool {
+        let size = self.px(self.theme.font_size);
+        let padding = self.px(self.theme.padding);
+        let height = self.px(self.theme.item_height);
+        let width = height + self.text_width(text, size) + padding * 2.0;
+        let rect = self.allocate(width, height);
+        let (_, highlighted, activated) = self.interact(rect);
+        if activated {
+            *value = !*value;
+        }
+        self.widget_background(rect, highlighted);
+
+        let box_size = size * 0.8;
+        let box_rect = Rect::new(
+            rect.x + padding,
+            rect.y + (height - box_size) / 2.0,
+            box_size,
+            box_size,
+        );
+        draw_rectangle_lines(
+            box_rect.x,
+            box_rect.y,
+            box_rect.w,
+            box_rect.h,
+            2.0,
+            self.theme.border,
+        );
+        if *value {
+            let inset = box_size * 0.25;
+            draw_rectangle(
+                box_rect.x + inset,
+                box_rect.y + inset,
+                box_rect.w - inset * 2.0,
+                box_rect.h - inset * 2.0,
+                self.theme.accent,
+            );
+        }
+        let text_pos = vec2(box_rect.right() + padding, rect.y + (height - size) / 2.0);
+        self.text(text, text_pos, size, self.theme.text);
+        return activated;
+    }
+
+    // dragged with the mouse, or left and right while focused in 20 steps.
+    // returns true when the value changed
+    pub fn slider(&mut self, text: &str, value: &mut f32, min: f32, max: f32) -> bool {
+        let size = self.px(self.theme.font_size);
+        let padding = self.px(self.theme.padding);
+        let rect = self.allocate(self.px(200.0), self.px(self.theme.item_height));
+        let (id, highlighted, _) = self.interact(rect);
+        if highlighted && self.clicked {
+            self.dragging = Some(id);
+        }
+        let before = *value;
+        if self.dragging == Some(id) {
+            let fraction = ((self.mouse.x - rect.x) / rect.w).clamp(0.0, 1.0);
+            *value = min + (max - min) * fraction;
+        } else if self.focus == Some(id) && self.keyboard {
+            let step = (max - min) / 20.0;
+            if is_key_pressed(KeyCode::Left) {
+                *value = (*value - step).max(min);
+            }
+            if is_key_pressed(KeyCode::Right) {
+                *value = (*value + step).min(max);
+            }
+        }
+
+        self.widget_background(rect, highlighted);
+        let fraction = ((*value - min) / (max - min)).clamp(0.0, 1.0);
+        let mut fill = self.theme.accent;
+        fill.a = 0.4;
+        draw_rectangle(rect.x, rect.y, rect.w * fraction, rect.h, fill);
+        let label = format!("{}: {:.2}", text, value);
+        let text_pos = vec2(rect.x + padding, rect.y + (rect.h - size) / 2.0);
+        self.text(&label, text_pos, size, self.theme.text);
+        return *value != before;
+    }
+
+    // typed into while focused, `placeholder` shows while it's empty.
+    // returns true when enter was pressed on it
+    pub fn text_input(
+        &mut self,
+        text: &str,
+        value: &mut String,
+        placeholder: &str,
+        max_length: usize,
+    ) -> bool {
+        let size = self.px(self.theme.font_size);
+        let padding = self.px(self.theme.padding);
+        let rect = self.allocate(self.px(200.0), self.px(self.theme.item_height));
+        let (id, highlighted, activated) = self.interact(rect);
+        let focused = self.focus == Some(id) && self.keyboard;
+        if focused {
+            for character in &self.typed {
+                if !character.is_control() && value.chars().count() < max_length {
+                    value.push(*character);
+                }
+            }
+            if is_key_pressed(KeyCode::Backspace) {
+                value.pop();
+            }
+        }
+
+        self.widget_background(rect, highlighted);
+        if focused {
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, self.theme.accent);
+        }
+        let label = format!("{}: ", text);
+        let text_pos = vec2(rect.x + padding, rect.y + (rect.h - size) / 2.0);
+        self.text(&label, text_pos, size, self.theme.text);
+        let value_pos = text_pos + vec2(self.text_width(&label, size), 0.0);
+        if value.is_empty() {
+            self.text(placeholder, value_pos, size, self.theme.dim_text);
+        } else {
+            self.text(value, value_pos, size, self.theme.text);
+        }
+        // a blinking caret
+        if focused && get_time().fract() < 0.5 {
+            let caret_x = value_pos.x + self.text_width(value, size) + 1.0;
+            draw_line(
+                caret_x,
+                text_pos.y,
+                caret_x,
+                text_pos.y + size,
+                1.0,
+                self.theme.text,
+            );
+        }
+        return activated && focused && !self.clicked;
+    }
+
+    // `rows` items at a time, scrolled with the wheel or by moving the selection
+    // past the edge. up and down move the selection while it's focused until
+    // they reach the ends. returns the item that was clicked or entered
+    pub fn list(
+        &mut self,
+        id: &str,
+        items: &[String],
+        selected: &mut usize,
+        rows: usize,
+    ) -> Option<usize> {
+        let size = self.px(self.theme.font_size);
+        let padding = self.px(self.theme.padding);
+        let row_height = self.px(self.theme.item_height);
+        let rect = self.allocate(self.px(200.0), row_height * rows as f32);
+        let (widget, highlighted, activated) = self.interact(rect);
+        let max_first = items.len().saturating_sub(rows) as f32;
+        let mut first = self.scroll.get(id).copied().unwrap_or(0.0);
+
+        if highlighted && rect.contains(self.mouse) {
+            let (_, wheel_y) = mouse_wheel();
+            first -= wheel_y.signum();
+        }
+        let mut chosen = None;
+        if self.focus == Some(widget) && !items.is_empty() {
+            let moved = *selected as i32 + self.nav;
+            if self.nav != 0 && moved >= 0 && moved < items.len() as i32 {
+                *selected = moved as usize;
+                self.nav = 0;
+                // keep the selection in view
+                first = first.clamp(*selected as f32 - rows as f32 + 1.0, *selected as f32);
+            }
+        }
+        first = first.clamp(0.0, max_first).round();
+        self.scroll.insert(id.to_owned(), first);
+
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, self.theme.widget);
+        let first = first as usize;
+        for (row, item) in items.iter().enumerate().skip(first).take(rows) {
+            let row_rect = Rect::new(
+                rect.x,
+                rect.y + (row - first) as f32 * row_height,
+                rect.w,
+                row_height,
+            );
+            let hovered = row_rect.contains(self.mouse);
+            if hovered && self.clicked {
+                *selected = row;
+                chosen = Some(row);
+            }
+            if row == *selected {
+                draw_rectangle(
+                    row_rect.x,
+                    row_rect.y,
+                    row_rect.w,
+                    row_rect.h,
+                    self.theme.hovered,
+                );
+            }
+            let text_pos = vec2(row_rect.x + padding, row_rect.y + (row_height - size) / 2.0);
+            self.text(item, text_pos, size, self.theme.text);
+        }
+        // a scrollbar when not everything fits
+        if items.len() > rows {
+            let bar_height = rect.h * rows as f32 / items.len() as f32;
+            let bar_y = rect.y + (rect.h - bar_height) * first as f32 / max_first;
+            draw_rectangle(
+                rect.right() - 4.0,
+                bar_y,
+                4.0,
+                bar_height,
+                self.theme.border,
+            );
+        }
+        if highlighted && self.focus == Some(widget) {
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, self.theme.accent);
+        }
+        if chosen.is_none() && activated && !self.clicked && !items.is_empty() {
+            chosen = Some(*selected);
+        }
+        return chosen;
+    }
+
+    // a strip of equally wide colors with a marker over `marker`, a fraction
+    // from 0 at the left edge to 1 at the right
+    pub fn color_bar(&mut self, colors: &[Color], marker: f32) {
+        let rect = self.allocate(self.px(180.0), self.px(16.0));
+        let width = rect.w / colors.len() as f32;
+        for (index, color) in colors.iter().enumerate() {
+            draw_rectangle(rect.x + index as f32 * width, rect.y, width, rect.h, *color);
+        }
+        let marker_x = rect.x + marker.clamp(0.0, 1.0) * (rect.w - width);
+        draw_rectangle(
+            marker_x,
+            rect.y,
+            width,
+            rect.h,
+            Color::new(1.0, 1.0, 1.0, 0.5),
+        );
+    }
+
+    pub fn color_swatch(&mut self, color: Color) {
+        let rect = self.allocate(self.px(180.0), self.px(16.0));
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, color);
+    }
+}
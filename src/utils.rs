@@ -7,6 +7,7 @@ pub fn draw_text_ex_shadow(text: &str, x: f32, y: f32, params: TextParams) {
     draw_text_ex(text, x, y, params);
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn aabb(x1: f32, y1: f32, w1: f32, h1: f32, x2: f32, y2: f32, w2: f32, h2: f32) -> bool {
     x1 < x2 + w2 && x1 + w1 > x2 && y1 < y2 + h2 && y1 + h1 > y2
 }
@@ -15,6 +16,11 @@ pub fn clamp(val: f32, min: f32, max: f32) -> f32 {
     return val.min(max).max(min);
 }
 
+// whether hex_color would accept it, for checking user input before it panics
+pub fn is_hex_color(hex: &str) -> bool {
+    return hex.len() == 7 && hex.starts_with('#') && u32::from_str_radix(&hex[1..], 16).is_ok();
+}
+
 pub fn hex_color(hex: &str, alpha: f32) -> Color {
     if hex.len() != 7 || !hex.starts_with("#") {
         panic!("Invalid hex color format");
@@ -36,12 +42,22 @@ pub fn hex_color(hex: &str, alpha: f32) -> Color {
     return Color::new(r_normalized, g_normalized, b_normalized, alpha);
 }
 
-#[macro_export]
-macro_rules! include_texture2d {
-    ($path:expr) => {{
-        let sprite =
-            Texture2D::from_file_with_format(include_bytes!($path), Some(ImageFormat::Png));
-        sprite.set_filter(FilterMode::Nearest);
-        sprite
-    }};
+// the opposite of hex_color, alpha is left out
+pub fn color_to_hex(color: Color) -> String {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    return format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(color.r),
+        channel(color.g),
+        channel(color.b)
+    );
+}
+
+pub fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    return Color::new(
+        a.r + (b.r - a.r) * t,
+        a.g + (b.g - a.g) * t,
+        a.b + (b.b - a.b) * t,
+        a.a + (b.a - a.a) * t,
+    );
 }
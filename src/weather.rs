@@ -0,0 +1,296 @@
+use crate::biome::Biome;
+use crate::block::Block;
+use crate::block::BlockOverlay;
+use crate::block::BlockType;
+use crate::particle::Particle;
+use crate::particle::ParticleKind;
+use crate::particle::ParticleSystem;
+use crate::position::GridPos;
+use crate::utils::hex_color;
+use crate::vis::VisRange;
+use crate::BLOCK_SIZE;
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+use std::collections::HashMap;
+
+// how quickly weather fades in and out, per second
+const FADE_SPEED: f32 = 0.1;
+// how long a weather lasts once it has fully faded in, in seconds
+const MIN_DURATION: f32 = 60.0;
+const MAX_DURATION: f32 = 180.0;
+// how quickly the wind catches up with the weather, per frame
+const WIND_EASE: f32 = 0.01;
+// chance that a snowflake landing on something stays there as snow
+const SNOW_STICK_CHANCE: f32 = 0.05;
+// how much a single flake adds to a snow layer
+const SNOW_STEP: f32 = 0.25;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum WeatherKind {
+    Clear,
+    Rain,
+    Storm,
+    Snow,
+    Blizzard,
+}
+
+impl WeatherKind {
+    pub const ALL: [WeatherKind; 5] = [
+        WeatherKind::Clear,
+        WeatherKind::Rain,
+        WeatherKind::Storm,
+        WeatherKind::Snow,
+        WeatherKind::Blizzard,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeatherKind::Clear => "clear",
+            WeatherKind::Rain => "rain",
+            WeatherKind::Storm => "storm",
+            WeatherKind::Snow => "snow",
+            WeatherKind::Blizzard => "blizzard",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<WeatherKind> {
+        return WeatherKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name);
+    }
+
+    fn is_snow(&self) -> bool {
+        return matches!(self, WeatherKind::Snow | WeatherKind::Blizzard);
+    }
+
+    // drops spawned per visible column per frame at full strength
+    fn density(&self) -> f32 {
+        match self {
+            WeatherKind::Clear => 0.0,
+            WeatherKind::Rain => 0.15,
+            WeatherKind::Storm => 0.4,
+            WeatherKind::Snow => 0.06,
+            WeatherKind::Blizzard => 0.2,
+        }
+    }
+
+    // strongest push on the player, in grid units per frame
+    fn wind(&self) -> f32 {
+        match self {
+            WeatherKind::Clear => 0.0,
+            WeatherKind::Rain => 0.005,
+            WeatherKind::Storm => 0.025,
+            WeatherKind::Snow => 0.004,
+            WeatherKind::Blizzard => 0.03,
+        }
+    }
+
+    // how far the sky is pulled towards STORM_SKY
+    fn darkness(&self) -> f32 {
+        match self {
+            WeatherKind::Clear => 0.0,
+            WeatherKind::Rain => 0.3,
+            WeatherKind::Storm => 0.7,
+            WeatherKind::Snow => 0.2,
+            WeatherKind::Blizzard => 0.5,
+        }
+    }
+}
+
+pub struct Weather {
+    pub kind: WeatherKind,
+    // what we're fading into once the current weather has faded out
+    next: Option<WeatherKind>,
+    // how strong the current weather is, 0 to 1
+    pub intensity: f32,
+    // seconds left before the weather changes
+    remaining: f32,
+    // current push on the player, signed
+    pub wind: f32,
+    wind_direction: f32,
+    // set by hand, so it lasts even where the biome wouldn't allow it
+    forced: bool,
+}
+
+impl Weather {
+    pub fn new() -> Self {
+        Weather {
+            kind: WeatherKind::Clear,
+            next: None,
+            intensity: 0.0,
+            remaining: MIN_DURATION,
+            wind: 0.0,
+            wind_direction: 1.0,
+            forced: false,
+        }
+    }
+
+    // fades over to `kind` and keeps it for a full spell
+    pub fn set(&mut self, kind: WeatherKind) {
+        self.next = Some(kind);
+        self.forced = true;
+    }
+
+    // how dark the sky should be, 0 is untouched
+    pub fn darkness(&self) -> f32 {
+        return self.kind.darkness() * self.intensity;
+    }
+
+    pub fn sky_colors(&self, top: Color, bottom: Color) -> (Color, Color) {
+        let storm_sky = hex_color("#2a2d33", 1.0);
+        let darkness = self.darkness();
+        let darken = |color: Color| {
+            Color::new(
+                color.r + (storm_sky.r - color.r) * darkness,
+                color.g + (storm_sky.g - color.g) * darkness,
+                color.b + (storm_sky.b - color.b) * darkness,
+                color.a + (1.0 - color.a) * darkness,
+            )
+        };
+        return (darken(top), darken(bottom));
+    }
+
+    // steps the state machine and spawns rain or snow. `biome` is where the player
+    // is and decides what weather comes next, `view` is the visible area in world
+    // coordinates
+    pub fn update(
+        &mut self,
+        delta: f32,
+        biome: Biome,
+        view: &VisRange,
+        particles: &mut ParticleSystem,
+    ) {
+        self.update_state(delta, biome);
+
+        let target_wind = self.kind.wind() * self.intensity * self.wind_direction;
+        self.wind += (target_wind - self.wind) * WIND_EASE;
+
+        self.spawn_drops(view, particles);
+    }
+
+    fn update_state(&mut self, delta: f32, biome: Biome) {
+        let allowed = self.forced
+            || biome
+                .params()
+                .weather
+                .iter()
+                .any(|(kind, weight)| *kind == self.kind && *weight > 0.0);
+
+        if self.next.is_none() {
+            self.remaining -= delta;
+            // walking into a biome where this weather can't happen ends it early
+            if self.remaining <= 0.0 || !allowed {
+                self.forced = false;
+                self.next = Some(pick_weather(biome));
+            }
+        }
+
+        match self.next {
+            // fade out, then switch over
+            Some(next) if next != self.kind => {
+                self.intensity -= FADE_SPEED * delta;
+                if self.intensity <= 0.0 {
+                    self.intensity = 0.0;
+                    self.kind = next;
+                }
+            }
+            // fade in, the timer only starts once we're fully there
+            Some(_) => {
+                self.intensity += FADE_SPEED * delta;
+                if self.intensity >= 1.0 {
+                    self.intensity = 1.0;
+                    self.next = None;
+                    self.remaining = gen_range(MIN_DURATION, MAX_DURATION);
+                    self.wind_direction = if gen_range(0, 2) == 0 { -1.0 } else { 1.0 };
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn spawn_drops(&self, view: &VisRange, particles: &mut ParticleSystem) {
+        let min_x = view.min_x / BLOCK_SIZE;
+        let max_x = view.max_x / BLOCK_SIZE;
+        let top = view.min_y / BLOCK_SIZE;
+        let bottom = view.max_y / BLOCK_SIZE;
+        // spawn a bit upwind so the edge of the screen isn't left empty
+        let upwind = -self.wind * 200.0;
+        let expected = self.kind.density() * self.intensity * (max_x - min_x);
+
+        let mut count = expected as usize;
+        if gen_range(0.0, 1.0) < expected.fract() {
+            count += 1;
+        }
+        let (kind, color, vel_y) = if self.kind.is_snow() {
+            (ParticleKind::Snow, Color::new(0.9, 0.92, 0.95, 0.9), 0.15)
+        } else {
+            // rain stays under a cell per frame so it can't skip through thin blocks
+            (ParticleKind::Rain, Color::new(0.6, 0.7, 0.85, 0.5), 0.9)
+        };
+        for _ in 0..count {
+            let y = top - gen_range(0.0, 4.0);
+            particles.spawn(Particle {
+                kind,
+                x: gen_range(min_x, max_x) + upwind,
+                y,
+                vel_x: 0.0,
+                vel_y,
+                color,
+                size: 1.0,
+                // gone once it's fallen past the bottom of the screen
+                life: ((bottom - y) / vel_y) as u32,
+            });
+        }
+    }
+}
+
+// a flake landed in `cell` coming from `last_cell`. sometimes it sticks, thickening
+// the snow there or starting a new layer on top of whatever it hit. returns the
+// cell that changed
+pub fn land_snow(
+    cell: GridPos,
+    last_cell: GridPos,
+    blocks: &mut HashMap<GridPos, Block>,
+) -> Option<GridPos> {
+    let block = *blocks.get(&cell)?;
+    if gen_range(0.0, 1.0) >= SNOW_STICK_CHANCE {
+        return None;
+    }
+    if block.block_type == BlockType::Snow {
+        if block.level >= 1.0 {
+            return None;
+        }
+        let mut snow = block;
+        snow.level = (snow.level + SNOW_STEP).min(1.0);
+        blocks.insert(cell, snow);
+        return Some(cell);
+    }
+
+    // only the top of solid ground collects snow, not liquids or the sides of walls
+    let above = GridPos::new(cell.x, cell.y - 1, false);
+    if !block.block_type.is_solid() || last_cell != above || blocks.contains_key(&above) {
+        return None;
+    }
+    let mut snow = Block::new(
+        BlockType::Snow,
+        hex_color("#dfe6ee", 1.0),
+        BlockOverlay::None,
+    );
+    snow.level = SNOW_STEP;
+    blocks.insert(above, snow);
+    return Some(above);
+}
+
+// random weather for a biome, weighted by its weather table
+fn pick_weather(biome: Biome) -> WeatherKind {
+    let table = biome.params().weather;
+    let total: f32 = table.iter().map(|(_, weight)| weight).sum();
+    let mut roll = gen_range(0.0, total);
+    for (kind, weight) in table {
+        if roll < *weight {
+            return *kind;
+        }
+        roll -= weight;
+    }
+    return WeatherKind::Clear;
+}
@@ -1,29 +1,77 @@
-use crate::block::render_block;
-use crate::block::render_block_overlay;
 use crate::block::Block;
-use crate::block::BlockOverlay;
 use crate::block::BlockType;
+use crate::camera::CameraController;
+use crate::clock::WorldClock;
+use crate::clock::DEFAULT_DAY_LENGTH;
+use crate::clock::START_TIME;
+use crate::debug::draw_world_overlays;
+use crate::debug::DebugView;
 use crate::entity::Entity;
+use crate::light::LightMap;
+use crate::light::SKY_LIMIT;
+use crate::mesh::BlockMeshes;
+use crate::mesh::MeshLayer;
+use crate::mesh::BG_SHADE;
+use crate::minimap::Minimap;
+use crate::parallax::load_parallax;
+use crate::parallax::Parallax;
+use crate::particle::ParticleKind;
+use crate::particle::ParticleSystem;
+use crate::physics::BlockPhysics;
 use crate::placer::Placer;
 use crate::player::Player;
 use crate::position::GridPos;
 use crate::position::SubGridPos;
+use crate::profiler::count;
+use crate::profiler::scope;
+use crate::terrain::chunk_of;
+use crate::terrain::TerrainGenerator;
+use crate::terrain::CHUNK_WIDTH;
+use crate::terrain::WORLD_BOTTOM;
 use crate::utils::hex_color;
+use crate::utils::lerp_color;
 use crate::vis::VisRange;
+use crate::weather::land_snow;
+use crate::weather::Weather;
 use crate::BLOCK_SIZE;
 use macroquad::prelude::*;
 use std::collections::HashMap;
+use std::collections::HashSet;
+
+// how many chunks around the player are kept generated on each side
+const GENERATE_RADIUS: i32 = 2;
+// chunks the camera looks at away from the player, generated a few per frame so
+// zooming out or panning far doesn't stall
+const VISIBLE_CHUNKS_PER_FRAME: usize = 2;
+// how quickly the sky blends into the next biome's colors each frame
+const SKY_FADE: f32 = 0.02;
+// random cells on screen checked each frame for lava that throws up an ember
+const EMBER_TRIES: usize = 40;
+// entities are drawn when their position is this many blocks from the screen or
+// closer, so big ones don't pop out at the edges
+const ENTITY_MARGIN: f32 = 16.0;
+// behind everything, also what world exports are drawn over
+pub const BG_COLOR: &str = "#15171c";
 
 pub struct World {
     pub blocks: HashMap<GridPos, Block>,
     pub bg_color: Color,
     pub entities: Vec<Box<dyn Entity>>,
+    next_entity_id: u64,
     pub player: Player,
     pub placer: Placer,
-    zoom: f32,
-    view_offset_x: f32,
-    view_offset_y: f32,
-    camera: Camera2D,
+    pub terrain: TerrainGenerator,
+    pub physics: BlockPhysics,
+    pub light: LightMap,
+    pub generated_chunks: HashSet<i32>,
+    pub clock: WorldClock,
+    pub weather: Weather,
+    pub particles: ParticleSystem,
+    pub meshes: BlockMeshes,
+    pub parallax: Parallax,
+    pub camera: CameraController,
+    pub minimap: Minimap,
+    pub debug: DebugView,
     sky_material: Material,
     sky_top_color: Color,
     sky_bottom_color: Color,
@@ -34,98 +82,330 @@ impl World {
         blocks: HashMap<GridPos, Block>,
         entities: Vec<Box<dyn Entity>>,
         bg_color: Color,
+        terrain: TerrainGenerator,
     ) -> Self {
         // Load sky shader
         let sky_material = load_sky_shader().unwrap();
+        let parallax = load_parallax(terrain.seed);
 
-        let world = World {
+        let mut world = World {
             blocks,
             bg_color,
-            entities,
-            player: Player::new(SubGridPos { x: 28.0, y: 1.0 }),
+            entities: Vec::new(),
+            next_entity_id: 1,
+            player: Player::new(SubGridPos { x: 0.0, y: 0.0 }),
             placer: Placer::new(),
-            view_offset_x: 0.0,
-            view_offset_y: 0.0,
-            zoom: 6.0,
-            camera: Camera2D {
-                ..Default::default()
-            },
+            terrain,
+            physics: BlockPhysics::new(),
+            light: LightMap::new(),
+            generated_chunks: HashSet::new(),
+            // start the day in the morning
+            clock: WorldClock::new(START_TIME, DEFAULT_DAY_LENGTH),
+            weather: Weather::new(),
+            particles: ParticleSystem::new(),
+            meshes: BlockMeshes::new(),
+            parallax,
+            camera: CameraController::new(),
+            minimap: Minimap::new(),
+            debug: DebugView::default(),
             sky_material,
             sky_top_color: hex_color("#0b0108", 1.0),
             sky_bottom_color: hex_color("#1b1f27", 0.0),
         };
+        for entity in entities {
+            world.add_entity(entity);
+        }
 
         return world;
     }
 
     pub fn update(&mut self) {
-        self.player.update(&self.blocks);
+        let _scope = scope("update");
+        let delta = get_frame_time();
+        self.clock.update(delta);
+        self.generate_nearby_chunks();
+        self.generate_visible_chunks();
+        self.update_sky_colors();
+        let biome = self.terrain.biome_at(self.player.pos.x as i32).primary;
+        let view = self.camera.visible();
+        self.weather
+            .update(delta, biome, &view, &mut self.particles);
+        for hit in self.particles.update(&self.blocks, self.weather.wind) {
+            if hit.kind != ParticleKind::Snow {
+                continue;
+            }
+            if let Some(pos) = land_snow(hit.cell, hit.from, &mut self.blocks) {
+                self.block_changed(pos);
+            }
+        }
+        for pos in self.physics.update(&mut self.blocks) {
+            self.block_changed(pos);
+        }
+        self.player
+            .update(&self.blocks, self.weather.wind, &mut self.particles);
+        if self.player.is_dead() {
+            self.spawn_player(0);
+        }
+        for (min_x, max_x) in self.light.update(&self.blocks) {
+            self.meshes.mark_dirty_range(min_x, max_x);
+        }
+        for entity in &mut self.entities {
+            entity.update(&mut self.particles);
+        }
+        self.minimap
+            .update(&self.generated_chunks, &self.blocks, self.player.pos);
     }
 
-    pub fn render(&mut self) {
-        clear_background(self.bg_color);
-        self.draw_sky();
-        self.set_camera_settings();
-
-        // calculate how much we can see on screen for culling
-        let world_vis = self.calc_screen_vis();
-        let mut blocks_to_render: Vec<(&GridPos, &Block)> = Vec::new();
-        // get all blocks to be rendered
-        for (pos, block) in &self.blocks {
-            if block.block_type == BlockType::Solid {
-                let scaled_pos = *pos * BLOCK_SIZE;
-                // cull blocks that aren't visible to the camera
-                if !world_vis.contains_coord(scaled_pos.x as f32, scaled_pos.y as f32) {
-                    continue;
-                }
-                blocks_to_render.push((pos, block));
-            }
+    // every block placed, removed or moved should be reported here
+    pub fn block_changed(&mut self, pos: GridPos) {
+        self.physics.wake(pos);
+        self.light.mark_dirty(pos.x);
+        self.meshes.mark_dirty_range(pos.x, pos.x);
+        self.minimap.mark_dirty(pos);
+    }
+
+    // lazily generate terrain around the player so the world never runs out
+    pub fn generate_nearby_chunks(&mut self) {
+        let player_chunk = chunk_of(self.player.pos.x as i32);
+        for chunk_x in player_chunk - GENERATE_RADIUS..=player_chunk + GENERATE_RADIUS {
+            self.generate_chunk(chunk_x);
         }
-        // these need to be rendered seperately to not fuck up draw calls(?)
-        for (pos, block) in &blocks_to_render {
-            render_block(**block, **pos);
+    }
+
+    // the rest of what's on screen, nearest to the middle of the view first
+    fn generate_visible_chunks(&mut self) {
+        let view = self.camera.visible();
+        let first = chunk_of((view.min_x / BLOCK_SIZE).floor() as i32);
+        let last = chunk_of((view.max_x / BLOCK_SIZE).floor() as i32);
+        let middle = (first + last) / 2;
+        let mut missing: Vec<i32> = (first..=last)
+            .filter(|chunk_x| !self.generated_chunks.contains(chunk_x))
+            .collect();
+        missing.sort_by_key(|chunk_x| (chunk_x - middle).abs());
+        for chunk_x in missing.into_iter().take(VISIBLE_CHUNKS_PER_FRAME) {
+            self.generate_chunk(chunk_x);
         }
-        for (pos, block) in &blocks_to_render {
-            let block = **block;
-            if block.overlay != BlockOverlay::None {
-                render_block_overlay(block, **pos);
+    }
+
+    // every chunk with a column in min_x..=max_x, for things like exports that
+    // look at parts of the world nobody has been to
+    pub fn generate_columns(&mut self, min_x: i32, max_x: i32) {
+        for chunk_x in chunk_of(min_x)..=chunk_of(max_x) {
+            self.generate_chunk(chunk_x);
+        }
+    }
+
+    // fills in the terrain of a chunk unless that already happened
+    pub fn generate_chunk(&mut self, chunk_x: i32) {
+        if self.generated_chunks.insert(chunk_x) {
+            self.terrain.generate_chunk(chunk_x, &mut self.blocks);
+            let start_x = chunk_x * CHUNK_WIDTH;
+            self.light
+                .mark_dirty_range(start_x, start_x + CHUNK_WIDTH - 1);
+        }
+    }
+
+    pub fn add_entity(&mut self, mut entity: Box<dyn Entity>) {
+        entity.spawn(self.next_entity_id);
+        self.next_entity_id += 1;
+        self.entities.push(entity);
+    }
+
+    // takes a block out, generating its chunk first so the terrain doesn't fill it
+    // back in later
+    pub fn remove_block(&mut self, pos: GridPos) -> Option<Block> {
+        self.generate_chunk(chunk_of(pos.x));
+        let removed = self.blocks.remove(&pos);
+        if removed.is_some() {
+            self.block_changed(pos);
+        }
+        return removed;
+    }
+
+    // puts down blocks from the placer, an imported image or the console. their
+    // chunks are generated first so terrain never ends up on top of them.
+    // blocks above or below the world are left out, returns how many were placed
+    pub fn place_blocks(&mut self, blocks: Vec<(GridPos, Block)>) -> usize {
+        let mut placed = 0;
+        for (pos, block) in blocks {
+            if !inside_world(pos) {
+                continue;
             }
+            self.generate_chunk(chunk_of(pos.x));
+            self.blocks.insert(pos, block);
+            self.block_changed(pos);
+            placed += 1;
         }
+        return placed;
+    }
 
-        self.player.render();
+    // the sky of the biome the player is in at the current time of day and weather
+    fn target_sky_colors(&self) -> (Color, Color) {
+        let (top, bottom) = self.terrain.sky_colors(self.player.pos.x);
+        let (top, bottom) = self.clock.sky_colors(top, bottom);
+        return self.weather.sky_colors(top, bottom);
+    }
 
-        // update and render placer
-        self.placer.update(&self.camera, &mut self.blocks);
+    // fade the sky towards the colors of the biome the player is in
+    fn update_sky_colors(&mut self) {
+        let (top, bottom) = self.target_sky_colors();
+        self.sky_top_color = lerp_color(self.sky_top_color, top, SKY_FADE);
+        self.sky_bottom_color = lerp_color(self.sky_bottom_color, bottom, SKY_FADE);
+    }
 
-        set_default_camera();
-        self.placer.render_hud();
+    // put the player on top of the terrain at the given column
+    pub fn spawn_player(&mut self, x: i32) {
+        let surface = (x..x + 3)
+            .map(|column| self.terrain.surface_height(column))
+            .min()
+            .unwrap();
+        self.player.respawn(SubGridPos {
+            x: x as f32,
+            y: (surface - 3) as f32,
+        });
+        self.generate_nearby_chunks();
+        self.camera.snap_to(self.player.pos);
+        let (top, bottom) = self.target_sky_colors();
+        self.sky_top_color = top;
+        self.sky_bottom_color = bottom;
     }
 
-    fn set_camera_settings(&mut self) {
-        let target_x = self.player.pos.x * BLOCK_SIZE;
-        let target_y = self.player.pos.y * BLOCK_SIZE;
-        self.view_offset_x += (target_x - self.view_offset_x) * 0.1;
-        self.view_offset_y += (target_y - self.view_offset_y) * 0.02;
-        self.camera.zoom = vec2(
-            self.zoom / screen_width(),
-            (screen_width() / screen_height() * self.zoom) / screen_width(),
+    // `input` is false while something else has the keyboard and mouse
+    pub fn render(&mut self, input: bool) {
+        clear_background(self.bg_color);
+        self.draw_sky();
+        self.camera.update(
+            self.player.pos,
+            self.player.get_vel().0,
+            self.placer.uses_mouse_wheel() || self.minimap.open,
+            input,
+        );
+
+        // only chunks that are on screen are built and drawn
+        let culling = scope("culling");
+        let world_vis = self.debug.cull_range(self.camera.visible());
+        let first_chunk = chunk_of((world_vis.min_x / BLOCK_SIZE).floor() as i32);
+        let last_chunk = chunk_of((world_vis.max_x / BLOCK_SIZE).floor() as i32);
+        let visible = first_chunk..=last_chunk;
+        self.meshes
+            .prepare(visible.clone(), &self.blocks, &self.light);
+        count("blocks considered", self.blocks.len() as u64);
+        count(
+            "blocks drawn",
+            self.meshes.block_count(visible.clone()) as u64,
         );
-        self.camera.target = vec2(self.view_offset_x, self.view_offset_y);
-        set_camera(&self.camera);
-    }
-
-    fn calc_screen_vis(&self) -> VisRange {
-        let min = self.camera.screen_to_world(Vec2::new(0.0, 0.0));
-        let max = self
-            .camera
-            .screen_to_world(Vec2::new(screen_width(), screen_height()));
-        // adding padding to avoid visible culling near screen sides
-        return VisRange::new(
-            min.x - BLOCK_SIZE,
-            min.y - BLOCK_SIZE,
-            max.x + BLOCK_SIZE,
-            max.y + BLOCK_SIZE,
+        drop(culling);
+
+        let ambient = self.clock.ambient();
+        self.parallax
+            .render(&self.camera.visible(), self.camera.center, ambient);
+        // background blocks go behind everything else
+        let blocks = scope("blocks");
+        self.meshes
+            .render(visible.clone(), MeshLayer::Background, ambient);
+        self.meshes
+            .render(visible.clone(), MeshLayer::Foreground, ambient);
+        drop(blocks);
+        let overlays = scope("overlays");
+        self.meshes
+            .render(visible.clone(), MeshLayer::Overlays, ambient);
+        drop(overlays);
+
+        for entity in &self.entities {
+            let pos = entity.get_pos();
+            let margin = ENTITY_MARGIN * BLOCK_SIZE;
+            let on_screen = VisRange::new(
+                world_vis.min_x - margin,
+                world_vis.min_y - margin,
+                world_vis.max_x + margin,
+                world_vis.max_y + margin,
+            )
+            .contains_coord(pos.x * BLOCK_SIZE, pos.y * BLOCK_SIZE);
+            if on_screen {
+                entity.render();
+            }
+        }
+        let player_center = GridPos::new(
+            (self.player.pos.x + 1.5) as i32,
+            (self.player.pos.y + 1.5) as i32,
+            false,
         );
+        self.player.render(self.block_light(player_center));
+
+        // liquids are see-through so they're drawn last, over the player
+        let liquids = scope("blocks");
+        self.meshes
+            .render(visible.clone(), MeshLayer::Liquids, ambient);
+        drop(liquids);
+        self.emit_embers(&world_vis);
+        self.particles
+            .render(&world_vis, |pos| self.block_light(pos));
+        draw_world_overlays(self, &world_vis, visible.clone());
+
+        // the full map takes over the mouse while it's open
+        if input && !self.minimap.open {
+            let _scope = scope("placer");
+            // the free camera drags with the middle button, which would pick colors
+            let pick = !self.camera.free;
+            match self.placer.update(self.camera.camera(), &self.blocks, pick) {
+                Some((pos, Some(block))) => {
+                    self.place_blocks(vec![(pos, block)]);
+                }
+                Some((pos, None)) => {
+                    if let Some(block) = self.remove_block(pos) {
+                        self.particles.emit_debris(pos, block.color);
+                    }
+                }
+                None => {}
+            }
+        }
+
+        set_default_camera();
+        let _scope = scope("hud");
+        let entities: Vec<SubGridPos> = self
+            .entities
+            .iter()
+            .map(|entity| entity.get_pos())
+            .collect();
+        if self.minimap.open {
+            self.minimap.render_full(self.player.pos, &entities);
+        } else {
+            self.minimap.render_corner(self.player.pos, &entities);
+        }
+    }
+
+    // lava surfaces on screen give off the odd spark
+    fn emit_embers(&mut self, world_vis: &VisRange) {
+        let min_x = (world_vis.min_x / BLOCK_SIZE).floor() as i32;
+        let max_x = (world_vis.max_x / BLOCK_SIZE).ceil() as i32;
+        let min_y = (world_vis.min_y / BLOCK_SIZE).floor() as i32;
+        let max_y = (world_vis.max_y / BLOCK_SIZE).ceil() as i32;
+        for _ in 0..EMBER_TRIES {
+            let pos = GridPos::new(
+                rand::gen_range(min_x, max_x),
+                rand::gen_range(min_y, max_y),
+                false,
+            );
+            let above = GridPos::new(pos.x, pos.y - 1, false);
+            let is_lava = self
+                .blocks
+                .get(&pos)
+                .is_some_and(|block| block.block_type == BlockType::Lava);
+            if is_lava && !self.blocks.contains_key(&above) {
+                self.particles.emit_ember(pos);
+            }
+        }
+    }
+
+    // how lit a cell is, ready to be multiplied into its color
+    fn block_light(&self, pos: GridPos) -> Color {
+        let mut light = self.light.get(pos.x, pos.y).to_color(self.clock.ambient());
+        if pos.bg {
+            light.r *= BG_SHADE;
+            light.g *= BG_SHADE;
+            light.b *= BG_SHADE;
+        }
+        return light;
     }
 
     fn draw_sky(&mut self) {
@@ -134,56 +414,53 @@ impl World {
         mat.set_uniform("canvasSize", (screen_width(), screen_height()));
         mat.set_uniform("startColor", self.sky_top_color);
         mat.set_uniform("endColor", self.sky_bottom_color);
+        let dpi = screen_dpi_scale();
+        mat.set_uniform("screenSize", (screen_width() * dpi, screen_height() * dpi));
+        mat.set_uniform("sunPos", self.clock.sun_pos());
+        mat.set_uniform("moonPos", self.clock.moon_pos());
+        mat.set_uniform("starAlpha", 1.0 - self.clock.daylight());
         draw_rectangle(0.0, 0.0, screen_width(), screen_height(), WHITE);
         gl_use_default_material();
     }
 }
 
-pub fn generate_test_world() -> World {
-    let mut blocks: HashMap<GridPos, Block> = HashMap::new();
-
-    // generate grass
-    for x in 0..64 {
-        for y in 0..15 {
-            let mut overlay = BlockOverlay::None;
-            if y == 0 {
-                overlay = BlockOverlay::None;
-            }
-            blocks.insert(
-                GridPos::new(x, y + 12, false),
-                Block {
-                    block_type: BlockType::Solid,
-                    color: hex_color("#1f3029", 1.0),
-                    overlay: overlay,
-                },
-            );
-        }
-    }
+// the rows blocks can be in. meshes, light and the map only cover these, so
+// nothing is ever placed above or below them
+pub fn inside_world(pos: GridPos) -> bool {
+    return (SKY_LIMIT..WORLD_BOTTOM).contains(&pos.y);
+}
 
-    // place blue blocks
-    let blue_block = Block::new(
-        BlockType::Solid,
-        hex_color("#5184c3", 1.0),
-        BlockOverlay::None,
+// a world with nothing generated yet
+pub fn new_world(seed: u32) -> World {
+    return World::new(
+        HashMap::new(),
+        Vec::new(),
+        hex_color(BG_COLOR, 1.0),
+        TerrainGenerator::new(seed),
     );
-    blocks.insert(GridPos::new(32, 10, false), blue_block.clone());
-    blocks.insert(GridPos::new(32, 11, false), blue_block.clone());
-    blocks.insert(GridPos::new(36, 11, false), blue_block.clone());
+}
 
-    return World::new(blocks, Vec::new(), hex_color("#15171c", 1.0));
+pub fn generate_world(seed: u32) -> World {
+    let mut world = new_world(seed);
+    world.spawn_player(0);
+    return world;
 }
 
 pub fn load_sky_shader() -> Result<macroquad::material::Material, macroquad::Error> {
     return load_material(
         ShaderSource::Glsl {
-            vertex: &include_str!("./assets/shaders/sky.vert"),
-            fragment: &include_str!("./assets/shaders/sky.frag"),
+            vertex: include_str!("./assets/shaders/sky.vert"),
+            fragment: include_str!("./assets/shaders/sky.frag"),
         },
         MaterialParams {
             uniforms: vec![
-                ("canvasSize".to_owned(), UniformType::Float2),
-                ("startColor".to_owned(), UniformType::Float4),
-                ("endColor".to_owned(), UniformType::Float4),
+                UniformDesc::new("canvasSize", UniformType::Float2),
+                UniformDesc::new("startColor", UniformType::Float4),
+                UniformDesc::new("endColor", UniformType::Float4),
+                UniformDesc::new("screenSize", UniformType::Float2),
+                UniformDesc::new("sunPos", UniformType::Float2),
+                UniformDesc::new("moonPos", UniformType::Float2),
+                UniformDesc::new("starAlpha", UniformType::Float1),
             ],
             ..Default::default()
         },